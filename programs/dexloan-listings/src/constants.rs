@@ -1 +1,4 @@
-pub const SECONDS_PER_DAY: i64 = 86_400;
\ No newline at end of file
+pub const SECONDS_PER_DAY: i64 = 86_400;
+pub const PRICE_FEED_MAX_AGE: i64 = 3_600;
+pub const SECONDS_PER_YEAR: i64 = 31_536_000;
+pub const BASIS_POINTS: u64 = 10_000;
\ No newline at end of file
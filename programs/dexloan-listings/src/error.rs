@@ -29,5 +29,39 @@ pub enum DexloanError {
     #[msg("Invalid escrow balance")]
     InvalidEscrowBalance,
     #[msg("Invalid token account delegate")]
-    InvalidDelegate
+    InvalidDelegate,
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Invalid duration")]
+    InvalidDuration,
+    #[msg("Offer expired")]
+    OfferExpired,
+    #[msg("Invalid collection")]
+    InvalidCollection,
+    #[msg("Invalid liquidation threshold")]
+    InvalidLiquidationThreshold,
+    #[msg("Price feed is stale")]
+    StalePriceFeed,
+    #[msg("This loan is not below the liquidation threshold")]
+    NotLiquidatable,
+    #[msg("Invalid loan note")]
+    InvalidLoanNote,
+    #[msg("Invalid basis points")]
+    InvalidBasisPoints,
+    #[msg("Auction has not ended")]
+    AuctionNotEnded,
+    #[msg("Auction has ended")]
+    AuctionEnded,
+    #[msg("Invalid bundle")]
+    InvalidBundle,
+    #[msg("This listing has expired")]
+    ListingExpired,
+    #[msg("This listing has not expired")]
+    ListingNotExpired,
+    #[msg("Listing terms do not match the expected terms")]
+    TermsMismatch,
+    #[msg("Option cannot be exercised at this time")]
+    OptionNotExercisable,
+    #[msg("Price feed is not owned by the oracle program")]
+    InvalidPriceFeed
 }
\ No newline at end of file
@@ -2,6 +2,7 @@ pub mod processor;
 pub mod error;
 pub mod state;
 pub mod constants;
+pub mod math;
 pub mod utils;
 
 use anchor_lang::prelude::*;
@@ -9,6 +10,7 @@ pub use processor::*;
 pub use error::*;
 pub use state::*;
 pub use constants::*;
+pub use math::*;
 pub use utils::*;
 
 declare_id!("8hSdpqHU7jz4C6C1kHUPQNMqBcC76n1BFXbHaTwd9X4c");
@@ -17,37 +19,100 @@ declare_id!("8hSdpqHU7jz4C6C1kHUPQNMqBcC76n1BFXbHaTwd9X4c");
 pub mod dexloan_listings {
     use super::*;
 
+    // Protocol Config
+    pub fn init_protocol_config<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitProtocolConfig<'info>>,
+        args: ProtocolConfigArgs
+    ) -> Result<()> {
+        handle_init_protocol_config(ctx, args)
+    }
+
+    pub fn update_protocol_config<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateProtocolConfig<'info>>,
+        args: ProtocolConfigArgs
+    ) -> Result<()> {
+        handle_update_protocol_config(ctx, args)
+    }
+
+    pub fn set_protocol_admin<'info>(ctx: Context<'_, '_, '_, 'info, SetProtocolAdmin<'info>>) -> Result<()> {
+        handle_set_protocol_admin(ctx)
+    }
+
     // Loans
     pub fn init_loan<'info>(
         ctx: Context<'_, '_, '_, 'info, InitLoan<'info>>,
-        amount: u64,
-        basis_points: u32,
-        duration: i64
+        args: LoanArgs
     ) -> Result<()> {
-        handle_init_loan(ctx, amount, basis_points, duration)
+        handle_init_loan(ctx, args)
     }
 
     pub fn init_loan_with_hire<'info>(
         ctx: Context<'_, '_, '_, 'info, InitLoanWithHire<'info>>,
-        amount: u64,
-        basis_points: u32,
-        duration: i64
+        args: LoanArgs
     ) -> Result<()> {
-        handle_init_loan_with_hire(ctx, amount, basis_points, duration)
+        handle_init_loan_with_hire(ctx, args)
     }
 
     pub fn close_loan<'info>(ctx: Context<'_, '_, '_, 'info, CloseLoan<'info>>) -> Result<()> {
         handle_close_loan(ctx)
     }
 
+    pub fn update_loan<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateLoan<'info>>,
+        args: LoanArgs
+    ) -> Result<()> {
+        handle_update_loan(ctx, args)
+    }
+
     pub fn give_loan<'info>(ctx: Context<'_, '_, '_, 'info, GiveLoan<'info>>) -> Result<()> {
         handle_give_loan(ctx)
     }
 
+    pub fn give_loan_with_terms<'info>(
+        ctx: Context<'_, '_, '_, 'info, GiveLoan<'info>>,
+        expected_terms: LoanArgs
+    ) -> Result<()> {
+        handle_give_loan_with_terms(ctx, expected_terms)
+    }
+
     pub fn repay_loan<'info>(ctx: Context<'_, '_, '_, 'info, RepayLoan<'info>>) -> Result<()> {
         handle_repay_loan(ctx)
     }
 
+    pub fn repay_loan_partial<'info>(ctx: Context<'_, '_, '_, 'info, RepayLoanPartial<'info>>, amount: u64) -> Result<()> {
+        handle_repay_loan_partial(ctx, amount)
+    }
+
+    pub fn refinance_loan<'info>(
+        ctx: Context<'_, '_, '_, 'info, RefinanceLoan<'info>>,
+        args: LoanArgs
+    ) -> Result<()> {
+        handle_refinance_loan(ctx, args)
+    }
+
+    pub fn propose_loan_extension<'info>(
+        ctx: Context<'_, '_, '_, 'info, ProposeLoanExtension<'info>>,
+        args: LoanExtensionArgs
+    ) -> Result<()> {
+        handle_propose_loan_extension(ctx, args)
+    }
+
+    pub fn accept_loan_extension<'info>(ctx: Context<'_, '_, '_, 'info, AcceptLoanExtension<'info>>) -> Result<()> {
+        handle_accept_loan_extension(ctx)
+    }
+
+    pub fn cancel_loan_extension<'info>(ctx: Context<'_, '_, '_, 'info, CancelLoanExtension<'info>>) -> Result<()> {
+        handle_cancel_loan_extension(ctx)
+    }
+
+    pub fn recall_loan<'info>(ctx: Context<'_, '_, '_, 'info, RecallLoan<'info>>) -> Result<()> {
+        handle_recall_loan(ctx)
+    }
+
+    pub fn migrate_loan(ctx: Context<MigrateLoan>) -> Result<()> {
+        handle_migrate_loan(ctx)
+    }
+
     pub fn repossess<'info>(ctx: Context<'_, '_, '_, 'info, Repossess<'info>>) -> Result<()> {
         handle_repossess(ctx)
     }
@@ -56,29 +121,170 @@ pub mod dexloan_listings {
         handle_repossess_with_hire(ctx)
     }
 
+    pub fn liquidate_loan<'info>(ctx: Context<'_, '_, '_, 'info, LiquidateLoan<'info>>) -> Result<()> {
+        handle_liquidate_loan(ctx)
+    }
+
+    pub fn buy_loan_collateral<'info>(ctx: Context<'_, '_, '_, 'info, BuyLoanCollateral<'info>>) -> Result<()> {
+        handle_buy_loan_collateral(ctx)
+    }
+
+    pub fn transfer_lender_position<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferLenderPosition<'info>>,
+        amount: u64
+    ) -> Result<()> {
+        handle_transfer_lender_position(ctx, amount)
+    }
+
+    pub fn mint_loan_note<'info>(ctx: Context<'_, '_, '_, 'info, MintLoanNote<'info>>) -> Result<()> {
+        handle_mint_loan_note(ctx)
+    }
+
+    // Bundle Loans
+    pub fn init_bundle_loan<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitBundleLoan<'info>>,
+        args: BundleLoanArgs
+    ) -> Result<()> {
+        handle_init_bundle_loan(ctx, args)
+    }
+
+    pub fn close_bundle_loan<'info>(ctx: Context<'_, '_, '_, 'info, CloseBundleLoan<'info>>) -> Result<()> {
+        handle_close_bundle_loan(ctx)
+    }
+
+    pub fn give_bundle_loan<'info>(ctx: Context<'_, '_, '_, 'info, GiveBundleLoan<'info>>) -> Result<()> {
+        handle_give_bundle_loan(ctx)
+    }
+
+    pub fn repay_bundle_loan<'info>(ctx: Context<'_, '_, '_, 'info, RepayBundleLoan<'info>>) -> Result<()> {
+        handle_repay_bundle_loan(ctx)
+    }
+
+    pub fn repossess_bundle_loan<'info>(ctx: Context<'_, '_, '_, 'info, RepossessBundleLoan<'info>>) -> Result<()> {
+        handle_repossess_bundle_loan(ctx)
+    }
+
+    // Loan Offers
+    pub fn init_loan_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitLoanOffer<'info>>,
+        args: LoanOfferArgs
+    ) -> Result<()> {
+        handle_init_loan_offer(ctx, args)
+    }
+
+    pub fn accept_loan_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptLoanOffer<'info>>,
+        expected_amount: u64,
+        expected_basis_points: u32,
+        expected_duration: i64
+    ) -> Result<()> {
+        handle_accept_loan_offer(ctx, expected_amount, expected_basis_points, expected_duration)
+    }
+
+    pub fn cancel_loan_offer<'info>(ctx: Context<'_, '_, '_, 'info, CancelLoanOffer<'info>>) -> Result<()> {
+        handle_cancel_loan_offer(ctx)
+    }
+
+    pub fn init_collection_loan_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitCollectionLoanOffer<'info>>,
+        args: CollectionLoanOfferArgs
+    ) -> Result<()> {
+        handle_init_collection_loan_offer(ctx, args)
+    }
+
+    pub fn accept_collection_loan_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptCollectionLoanOffer<'info>>,
+        expected_amount: u64,
+        expected_basis_points: u32,
+        expected_duration: i64
+    ) -> Result<()> {
+        handle_accept_collection_loan_offer(ctx, expected_amount, expected_basis_points, expected_duration)
+    }
+
+    pub fn cancel_collection_loan_offer<'info>(ctx: Context<'_, '_, '_, 'info, CancelCollectionLoanOffer<'info>>) -> Result<()> {
+        handle_cancel_collection_loan_offer(ctx)
+    }
+
     // Call Options
     pub fn init_call_option(
         ctx: Context<InitCallOption>,
-        amount: u64,
-        strike_price: u64,
-        expiry: i64
+        args: CallOptionArgs
     ) -> Result<()> {
-        handle_init_call_option(ctx, amount, strike_price, expiry)
+        handle_init_call_option(ctx, args)
     }
 
     pub fn init_call_option_with_hire(
         ctx: Context<InitCallOptionWithHire>,
-        amount: u64,
-        strike_price: u64,
-        expiry: i64
+        args: CallOptionArgs
+    ) -> Result<()> {
+        handle_init_call_option_with_hire(ctx, args)
+    }
+
+    pub fn migrate_call_option(ctx: Context<MigrateCallOption>) -> Result<()> {
+        handle_migrate_call_option(ctx)
+    }
+
+    pub fn update_call_option(
+        ctx: Context<UpdateCallOption>,
+        args: CallOptionArgs
     ) -> Result<()> {
-        handle_init_call_option_with_hire(ctx, amount, strike_price, expiry)
+        handle_update_call_option(ctx, args)
     }
 
     pub fn buy_call_option<'info>(ctx: Context<'_, '_, '_, 'info, BuyCallOption<'info>>) -> Result<()> {
         handle_buy_call_option(ctx)
     }
 
+    pub fn buy_call_option_with_terms<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyCallOption<'info>>,
+        expected_terms: CallOptionArgs
+    ) -> Result<()> {
+        handle_buy_call_option_with_terms(ctx, expected_terms)
+    }
+
+    pub fn start_call_option_auction(
+        ctx: Context<StartCallOptionAuction>,
+        duration: i64
+    ) -> Result<()> {
+        handle_start_call_option_auction(ctx, duration)
+    }
+
+    pub fn bid_call_option(ctx: Context<BidCallOption>, amount: u64) -> Result<()> {
+        handle_bid_call_option(ctx, amount)
+    }
+
+    pub fn settle_call_option_auction(ctx: Context<SettleCallOptionAuction>) -> Result<()> {
+        handle_settle_call_option_auction(ctx)
+    }
+
+    pub fn list_call_option_position(
+        ctx: Context<ListCallOptionPosition>,
+        price: Option<u64>
+    ) -> Result<()> {
+        handle_list_call_option_position(ctx, price)
+    }
+
+    pub fn buy_call_option_position<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyCallOptionPosition<'info>>,
+        expected_price: u64
+    ) -> Result<()> {
+        handle_buy_call_option_position(ctx, expected_price)
+    }
+
+    pub fn list_call_option_buy_back(
+        ctx: Context<ListCallOptionBuyBack>,
+        price: Option<u64>
+    ) -> Result<()> {
+        handle_list_call_option_buy_back(ctx, price)
+    }
+
+    pub fn buy_back_call_option<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyBackCallOption<'info>>,
+        expected_price: u64
+    ) -> Result<()> {
+        handle_buy_back_call_option(ctx, expected_price)
+    }
+
     pub fn exercise_call_option<'info>(ctx: Context<'_, '_, '_, 'info, ExerciseCallOption<'info>>) -> Result<()> {
         handle_exercise_call_option(ctx)
     }
@@ -91,6 +297,33 @@ pub mod dexloan_listings {
         handle_close_call_option(ctx)
     }
 
+    // Put Options
+    pub fn init_put_option(
+        ctx: Context<InitPutOption>,
+        amount: u64,
+        strike_price: u64,
+        expiry: i64
+    ) -> Result<()> {
+        handle_init_put_option(ctx, amount, strike_price, expiry)
+    }
+
+    pub fn buy_put_option(
+        ctx: Context<BuyPutOption>,
+        expected_strike_price: u64,
+        expected_premium: u64,
+        expected_expiry: i64,
+    ) -> Result<()> {
+        handle_buy_put_option(ctx, expected_strike_price, expected_premium, expected_expiry)
+    }
+
+    pub fn exercise_put_option<'info>(ctx: Context<'_, '_, '_, 'info, ExercisePutOption<'info>>) -> Result<()> {
+        handle_exercise_put_option(ctx)
+    }
+
+    pub fn close_put_option(ctx: Context<ClosePutOption>) -> Result<()> {
+        handle_close_put_option(ctx)
+    }
+
     // Hires
     pub fn init_hire<'info>(
         ctx: Context<'_, '_, '_, 'info, InitHire<'info>>,
@@ -99,10 +332,26 @@ pub mod dexloan_listings {
         handle_init_hire(ctx, args)
     }
 
+    pub fn update_hire<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateHire<'info>>,
+        args: HireArgs
+    ) -> Result<()> {
+        handle_update_hire(ctx, args)
+    }
+
     pub fn take_hire<'info>(ctx: Context<'_, '_, '_, 'info, TakeHire<'info>>, days: u16) -> Result<()> {
         handle_take_hire(ctx, days)
     }
 
+    pub fn take_hire_with_terms<'info>(
+        ctx: Context<'_, '_, '_, 'info, TakeHire<'info>>,
+        days: u16,
+        max_daily_rate: u64,
+        expected_expiry: i64
+    ) -> Result<()> {
+        handle_take_hire_with_terms(ctx, days, max_daily_rate, expected_expiry)
+    }
+
     pub fn extend_hire<'info>(ctx: Context<'_, '_, '_, 'info, ExtendHire<'info>>, days: u16) -> Result<()> {
         handle_extend_hire(ctx, days)
     }
@@ -118,4 +367,9 @@ pub mod dexloan_listings {
     pub fn close_hire<'info>(ctx: Context<'_, '_, '_, 'info, CloseHire<'info>>) -> Result<()> {
         handle_close_hire(ctx)
     }
+
+    // Listings
+    pub fn close_expired_listing<'info>(ctx: Context<'_, '_, '_, 'info, CloseExpiredListing<'info>>) -> Result<()> {
+        handle_close_expired_listing(ctx)
+    }
 }
\ No newline at end of file
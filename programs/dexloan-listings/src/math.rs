@@ -0,0 +1,154 @@
+use std::convert::TryFrom;
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::error::*;
+
+/// The direction to round the result of a division in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round towards zero, in favour of the party paying e.g. the borrower
+    Down,
+    /// Round away from zero, in favour of the party being paid e.g. the lender
+    Up,
+}
+
+/// Calculates `value * numerator / denominator` using u128 checked arithmetic
+pub fn mul_div(
+    value: u128,
+    numerator: u128,
+    denominator: u128,
+    rounding: Rounding,
+) -> Result<u64> {
+    let product = value
+        .checked_mul(numerator)
+        .ok_or(DexloanError::NumericalOverflow)?;
+    let mut quotient = product
+        .checked_div(denominator)
+        .ok_or(DexloanError::NumericalOverflow)?;
+
+    if rounding == Rounding::Up && product % denominator != 0 {
+        quotient = quotient
+            .checked_add(1)
+            .ok_or(DexloanError::NumericalOverflow)?;
+    }
+
+    u64::try_from(quotient).map_err(|_| DexloanError::NumericalOverflow.into())
+}
+
+/// Calculates interest on an amount at an annual rate in basis points over a duration in seconds
+pub fn calculate_interest(
+    amount: u64,
+    basis_points: u32,
+    duration: i64,
+    rounding: Rounding,
+) -> Result<u64> {
+    if duration < 0 {
+        return err!(DexloanError::InvalidDuration);
+    }
+
+    // amount * basis_points is at most 96 bits so cannot overflow
+    let annual_interest = (amount as u128) * (basis_points as u128);
+
+    mul_div(
+        annual_interest,
+        duration as u128,
+        (BASIS_POINTS as u128) * (SECONDS_PER_YEAR as u128),
+        rounding,
+    )
+}
+
+/// Calculates the share of an amount that has elapsed between two timestamps
+pub fn calculate_pro_rata(
+    amount: u64,
+    start: i64,
+    end: i64,
+    now: i64,
+    rounding: Rounding,
+) -> Result<u64> {
+    if end <= start {
+        return err!(DexloanError::InvalidDuration);
+    }
+
+    let now = now.clamp(start, end);
+    // Widened to avoid overflow when the timestamps span the full i64 range
+    let elapsed = (now as i128 - start as i128) as u128;
+    let total = (end as i128 - start as i128) as u128;
+
+    mul_div(amount as u128, elapsed, total, rounding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_error(result: Result<u64>, expected: DexloanError) {
+        match result {
+            Err(Error::AnchorError(error)) => assert_eq!(error.error_code_number, u32::from(expected)),
+            _ => panic!("expected {:?}", expected),
+        }
+    }
+
+    #[test]
+    fn mul_div_rounds_in_the_given_direction() {
+        assert_eq!(mul_div(10, 1, 3, Rounding::Down).unwrap(), 3);
+        assert_eq!(mul_div(10, 1, 3, Rounding::Up).unwrap(), 4);
+        assert_eq!(mul_div(9, 1, 3, Rounding::Up).unwrap(), 3);
+    }
+
+    #[test]
+    fn mul_div_errors_instead_of_overflowing() {
+        assert_error(mul_div(u128::MAX, 2, 1, Rounding::Down), DexloanError::NumericalOverflow);
+        assert_error(mul_div(u64::MAX as u128, 2, 1, Rounding::Down), DexloanError::NumericalOverflow);
+        assert_error(mul_div(1, 1, 0, Rounding::Down), DexloanError::NumericalOverflow);
+    }
+
+    #[test]
+    fn basis_points_of_u64_max() {
+        let amount = u64::MAX as u128;
+        let divisor = BASIS_POINTS as u128;
+
+        assert_eq!(mul_div(amount, 10_000, divisor, Rounding::Down).unwrap(), u64::MAX);
+        assert_eq!(mul_div(amount, 5_000, divisor, Rounding::Down).unwrap(), u64::MAX / 2);
+        assert_eq!(mul_div(amount, 5_000, divisor, Rounding::Up).unwrap(), u64::MAX / 2 + 1);
+        assert_error(mul_div(amount, 10_001, divisor, Rounding::Down), DexloanError::NumericalOverflow);
+    }
+
+    #[test]
+    fn calculate_interest_for_a_full_year() {
+        let amount = 1_000_000_000;
+
+        assert_eq!(calculate_interest(amount, 500, SECONDS_PER_YEAR, Rounding::Down).unwrap(), 50_000_000);
+        assert_eq!(calculate_interest(amount, 500, 0, Rounding::Up).unwrap(), 0);
+    }
+
+    #[test]
+    fn calculate_interest_rounding_favours_the_given_party() {
+        // 1 lamport at 1% for one second is a tiny fraction of a lamport
+        assert_eq!(calculate_interest(1, 100, 1, Rounding::Down).unwrap(), 0);
+        assert_eq!(calculate_interest(1, 100, 1, Rounding::Up).unwrap(), 1);
+    }
+
+    #[test]
+    fn calculate_interest_at_u64_max_amount() {
+        assert_eq!(calculate_interest(u64::MAX, 10_000, SECONDS_PER_YEAR, Rounding::Down).unwrap(), u64::MAX);
+        assert_eq!(calculate_interest(u64::MAX, 1, SECONDS_PER_DAY, Rounding::Down).unwrap(), 5_053_902_485_947);
+        assert_error(calculate_interest(u64::MAX, u32::MAX, SECONDS_PER_YEAR, Rounding::Down), DexloanError::NumericalOverflow);
+    }
+
+    #[test]
+    fn calculate_interest_at_i64_max_duration() {
+        assert_eq!(calculate_interest(1_000_000_000, 500, i64::MAX, Rounding::Down).unwrap(), 14_623_560_433_876_800_810);
+        assert_error(calculate_interest(u64::MAX, u32::MAX, i64::MAX, Rounding::Down), DexloanError::NumericalOverflow);
+        assert_error(calculate_interest(1, 1, i64::MIN, Rounding::Down), DexloanError::InvalidDuration);
+    }
+
+    #[test]
+    fn calculate_pro_rata_at_extremes() {
+        assert_eq!(calculate_pro_rata(u64::MAX, i64::MIN, i64::MAX, i64::MAX, Rounding::Down).unwrap(), u64::MAX);
+        assert_eq!(calculate_pro_rata(u64::MAX, i64::MIN, i64::MAX, -1, Rounding::Down).unwrap(), u64::MAX / 2);
+        assert_eq!(calculate_pro_rata(u64::MAX, 0, i64::MAX, i64::MIN, Rounding::Down).unwrap(), 0);
+        assert_eq!(calculate_pro_rata(100, 0, 3, 1, Rounding::Down).unwrap(), 33);
+        assert_eq!(calculate_pro_rata(100, 0, 3, 1, Rounding::Up).unwrap(), 34);
+        assert_error(calculate_pro_rata(100, 1, 1, 1, Rounding::Down), DexloanError::InvalidDuration);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,44 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Token};
+use crate::state::{BundleLoan, LoanState};
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct CloseBundleLoan<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            BundleLoan::PREFIX,
+            borrower.key().as_ref(),
+            bundle_loan.id.to_le_bytes().as_ref(),
+        ],
+        bump = bundle_loan.bump,
+        has_one = borrower,
+        constraint = bundle_loan.state == LoanState::Listed || bundle_loan.state == LoanState::Defaulted,
+        close = borrower
+    )]
+    pub bundle_loan: Box<Account<'info, BundleLoan>>,
+    /// CHECK: validated in cpi
+    pub metadata_program: UncheckedAccount<'info>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle_close_bundle_loan<'info>(ctx: Context<'_, '_, '_, 'info, CloseBundleLoan<'info>>) -> Result<()> {
+    let bundle_loan = &ctx.accounts.bundle_loan;
+
+    // Defaulted bundles have already been transferred to the lender
+    if bundle_loan.state == LoanState::Listed {
+        thaw_and_revoke_bundle(
+            &bundle_loan.mints,
+            &mut ctx.remaining_accounts.iter(),
+            &ctx.accounts.borrower.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+        )?;
+    }
+
+    Ok(())
+}
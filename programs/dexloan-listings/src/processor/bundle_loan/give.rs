@@ -0,0 +1,48 @@
+use anchor_lang::{prelude::*};
+use crate::state::{BundleLoan, LoanState};
+
+#[derive(Accounts)]
+pub struct GiveBundleLoan<'info> {
+    /// CHECK: contrained on bundle_loan
+    #[account(mut)]
+    pub borrower: AccountInfo<'info>,
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            BundleLoan::PREFIX,
+            borrower.key().as_ref(),
+            bundle_loan.id.to_le_bytes().as_ref(),
+        ],
+        bump = bundle_loan.bump,
+        has_one = borrower,
+        constraint = bundle_loan.borrower != lender.key(),
+        constraint = bundle_loan.state == LoanState::Listed,
+    )]
+    pub bundle_loan: Box<Account<'info, BundleLoan>>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_give_bundle_loan(ctx: Context<GiveBundleLoan>) -> Result<()> {
+    let bundle_loan = &mut ctx.accounts.bundle_loan;
+
+    bundle_loan.state = LoanState::Active;
+    bundle_loan.lender = ctx.accounts.lender.key();
+    bundle_loan.start_date = ctx.accounts.clock.unix_timestamp;
+
+    anchor_lang::solana_program::program::invoke(
+        &anchor_lang::solana_program::system_instruction::transfer(
+            &bundle_loan.lender,
+            &bundle_loan.borrower,
+            bundle_loan.amount,
+        ),
+        &[
+            ctx.accounts.lender.to_account_info(),
+            ctx.accounts.borrower.to_account_info(),
+        ]
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Token};
+use crate::state::{BundleLoan, LoanState};
+use crate::utils::*;
+use crate::error::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BundleLoanArgs {
+    pub id: u64,
+    pub amount: u64,
+    pub basis_points: u32,
+    pub duration: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(args: BundleLoanArgs)]
+pub struct InitBundleLoan<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+    #[account(
+        init,
+        payer = borrower,
+        seeds = [
+            BundleLoan::PREFIX,
+            borrower.key().as_ref(),
+            args.id.to_le_bytes().as_ref(),
+        ],
+        space = BundleLoan::space(),
+        bump,
+    )]
+    pub bundle_loan: Box<Account<'info, BundleLoan>>,
+    /// CHECK: validated in cpi
+    pub metadata_program: UncheckedAccount<'info>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_init_bundle_loan<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitBundleLoan<'info>>,
+    args: BundleLoanArgs,
+) -> Result<()> {
+    let bundle_loan = &mut ctx.accounts.bundle_loan;
+    let borrower = ctx.accounts.borrower.to_account_info();
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+    let count = ctx.remaining_accounts.len() / 4;
+
+    if count == 0 || count > BundleLoan::MAX_MINTS || ctx.remaining_accounts.len() % 4 != 0 {
+        return err!(DexloanError::InvalidBundle);
+    }
+
+    if args.amount == 0 {
+        return err!(DexloanError::InvalidAmount);
+    }
+
+    if args.duration <= 0 {
+        return err!(DexloanError::InvalidDuration);
+    }
+
+    // Init
+    bundle_loan.id = args.id;
+    bundle_loan.borrower = borrower.key();
+    bundle_loan.bump = *ctx.bumps.get("bundle_loan").unwrap();
+    //
+    bundle_loan.amount = args.amount;
+    bundle_loan.basis_points = args.basis_points;
+    bundle_loan.duration = args.duration;
+    bundle_loan.state = LoanState::Listed;
+
+    let mut mints = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        let item = next_bundle_item(remaining_accounts, &borrower.key())?;
+
+        assert_bundle_mint(&item.mint)?;
+
+        if mints.contains(&item.mint.key()) {
+            return err!(DexloanError::InvalidBundle);
+        }
+
+        let mut token_manager = init_token_manager_if_needed(
+            &item.token_manager,
+            &item.mint,
+            &borrower,
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        // Bundled NFTs can't be used in any other listing
+        require_eq!(token_manager.accounts.loan, false, DexloanError::InvalidState);
+        require_eq!(token_manager.bundle, false, DexloanError::InvalidState);
+        require_eq!(token_manager.accounts.hire, false, DexloanError::InvalidState);
+        require_eq!(token_manager.accounts.call_option, false, DexloanError::InvalidState);
+
+        token_manager.bundle = true;
+
+        delegate_and_freeze_token_account(
+            &mut token_manager,
+            ctx.accounts.token_program.to_account_info(),
+            item.token_account.to_account_info(),
+            borrower.clone(),
+            item.mint.clone(),
+            item.edition.clone(),
+            borrower.clone(),
+        )?;
+
+        token_manager.exit(&crate::ID)?;
+        mints.push(item.mint.key());
+    }
+
+    bundle_loan.mints = mints;
+
+    Ok(())
+}
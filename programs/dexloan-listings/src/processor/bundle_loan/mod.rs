@@ -0,0 +1,11 @@
+pub mod close;
+pub mod give;
+pub mod initialize;
+pub mod repay;
+pub mod repossess;
+
+pub use close::*;
+pub use give::*;
+pub use initialize::*;
+pub use repay::*;
+pub use repossess::*;
@@ -0,0 +1,81 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Token};
+use crate::state::{BundleLoan, LoanState, ProtocolConfig};
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct RepayBundleLoan<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+    /// CHECK: contrained on bundle_loan
+    #[account(mut)]
+    pub lender: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            BundleLoan::PREFIX,
+            borrower.key().as_ref(),
+            bundle_loan.id.to_le_bytes().as_ref(),
+        ],
+        bump = bundle_loan.bump,
+        has_one = borrower,
+        has_one = lender,
+        constraint = bundle_loan.state == LoanState::Active,
+        close = borrower
+    )]
+    pub bundle_loan: Box<Account<'info, BundleLoan>>,
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    /// CHECK: validated in cpi
+    pub metadata_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle_repay_bundle_loan<'info>(ctx: Context<'_, '_, '_, 'info, RepayBundleLoan<'info>>) -> Result<()> {
+    let bundle_loan = &ctx.accounts.bundle_loan;
+
+    let amount_due = calculate_loan_repayment(
+        bundle_loan.amount,
+        bundle_loan.basis_points,
+        bundle_loan.duration
+    )?;
+    // The protocol fee is only taken from interest
+    let protocol_fee = calculate_fee_from_basis_points(
+        (amount_due - bundle_loan.amount) as u128,
+        ctx.accounts.protocol_config.loan_fee_basis_points as u128,
+    )?;
+
+    transfer_currency(
+        &None,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.borrower.to_account_info(),
+        &ctx.accounts.borrower.to_account_info(),
+        &ctx.accounts.lender.to_account_info(),
+        amount_due - protocol_fee,
+    )?;
+    transfer_currency(
+        &None,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.borrower.to_account_info(),
+        &ctx.accounts.borrower.to_account_info(),
+        &ctx.accounts.treasury.to_account_info(),
+        protocol_fee,
+    )?;
+
+    thaw_and_revoke_bundle(
+        &bundle_loan.mints,
+        &mut ctx.remaining_accounts.iter(),
+        &ctx.accounts.borrower.to_account_info(),
+        &ctx.accounts.token_program.to_account_info(),
+    )?;
+
+    Ok(())
+}
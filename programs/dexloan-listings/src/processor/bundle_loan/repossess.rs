@@ -0,0 +1,79 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Token, TokenAccount};
+use crate::state::{BundleLoan, LoanState};
+use crate::error::{DexloanError};
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct RepossessBundleLoan<'info> {
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    /// CHECK: contrained on bundle_loan
+    #[account(mut)]
+    pub borrower: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            BundleLoan::PREFIX,
+            borrower.key().as_ref(),
+            bundle_loan.id.to_le_bytes().as_ref(),
+        ],
+        bump = bundle_loan.bump,
+        has_one = borrower,
+        has_one = lender,
+        constraint = bundle_loan.state == LoanState::Active,
+    )]
+    pub bundle_loan: Box<Account<'info, BundleLoan>>,
+    /// CHECK: validated in cpi
+    pub metadata_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_repossess_bundle_loan<'info>(ctx: Context<'_, '_, '_, 'info, RepossessBundleLoan<'info>>) -> Result<()> {
+    let bundle_loan = &mut ctx.accounts.bundle_loan;
+    let borrower = ctx.accounts.borrower.to_account_info();
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    let maturity = bundle_loan.start_date
+        .checked_add(bundle_loan.duration)
+        .ok_or(DexloanError::NumericalOverflow)?;
+
+    if maturity > unix_timestamp {
+        return Err(DexloanError::NotOverdue.into())
+    }
+
+    bundle_loan.state = LoanState::Defaulted;
+
+    // Each NFT's accounts are followed by the lender's token account for its mint
+    for mint in bundle_loan.mints.iter() {
+        let item = next_bundle_item(remaining_accounts, &borrower.key())?;
+        let lender_token_account = Account::<TokenAccount>::try_from(next_account_info(remaining_accounts)?)?;
+
+        if item.mint.key() != *mint || lender_token_account.mint != *mint {
+            return err!(DexloanError::InvalidMint);
+        }
+
+        require_keys_eq!(lender_token_account.owner, bundle_loan.lender);
+
+        let mut token_manager = load_token_manager(&item.token_manager, &item.mint, &borrower)?;
+
+        token_manager.bundle = false;
+
+        thaw_and_transfer_from_token_account(
+            &mut token_manager,
+            ctx.accounts.token_program.to_account_info(),
+            borrower.clone(),
+            item.token_account.to_account_info(),
+            lender_token_account.to_account_info(),
+            item.mint.clone(),
+            item.edition.clone(),
+        )?;
+
+        token_manager.exit(&crate::ID)?;
+    }
+
+    Ok(())
+}
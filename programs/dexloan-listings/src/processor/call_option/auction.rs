@@ -0,0 +1,229 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint};
+use crate::state::{CallOption, CallOptionBid, CallOptionState, ProtocolConfig};
+use crate::error::*;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct StartCallOptionAuction<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            CallOption::PREFIX,
+            mint.key().as_ref(),
+            seller.key().as_ref(),
+        ],
+        bump,
+        has_one = seller,
+        has_one = mint,
+        constraint = call_option.state == CallOptionState::Listed,
+    )]
+    pub call_option: Box<Account<'info, CallOption>>,
+    #[account(
+        init,
+        payer = seller,
+        seeds = [
+            CallOptionBid::PREFIX,
+            call_option.key().as_ref(),
+        ],
+        space = CallOptionBid::space(),
+        bump,
+    )]
+    pub call_option_bid: Box<Account<'info, CallOptionBid>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+// The listing amount becomes the reserve price of the auction
+pub fn handle_start_call_option_auction(ctx: Context<StartCallOptionAuction>, duration: i64) -> Result<()> {
+    let call_option = &mut ctx.accounts.call_option;
+    let call_option_bid = &mut ctx.accounts.call_option_bid;
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    if call_option.is_listing_expired(unix_timestamp) {
+        return err!(DexloanError::ListingExpired);
+    }
+
+    // Bids are escrowed in lamports
+    if call_option.currency_mint.is_some() {
+        return err!(DexloanError::InvalidMint);
+    }
+
+    require_gt!(duration, 0, DexloanError::InvalidDuration);
+
+    let auction_end = unix_timestamp
+        .checked_add(duration)
+        .ok_or(DexloanError::NumericalOverflow)?;
+
+    if auction_end > call_option.expiry {
+        return err!(DexloanError::InvalidExpiry);
+    }
+
+    call_option.state = CallOptionState::Auctioning;
+    call_option.auction_end = auction_end;
+    //
+    call_option_bid.call_option = call_option.key();
+    call_option_bid.bidder = None;
+    call_option_bid.amount = 0;
+    call_option_bid.bump = *ctx.bumps.get("call_option_bid").unwrap();
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct BidCallOption<'info> {
+    /// CHECK: contrained on call_option
+    pub seller: AccountInfo<'info>,
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+    /// CHECK: contrained on call_option_bid
+    #[account(mut)]
+    pub previous_bidder: AccountInfo<'info>,
+    #[account(
+        seeds = [
+            CallOption::PREFIX,
+            mint.key().as_ref(),
+            seller.key().as_ref(),
+        ],
+        bump,
+        has_one = seller,
+        has_one = mint,
+        constraint = call_option.seller != bidder.key(),
+        constraint = call_option.counterparty.is_none() || call_option.counterparty == Some(bidder.key()),
+        constraint = call_option.state == CallOptionState::Auctioning,
+    )]
+    pub call_option: Box<Account<'info, CallOption>>,
+    #[account(
+        mut,
+        seeds = [
+            CallOptionBid::PREFIX,
+            call_option.key().as_ref(),
+        ],
+        bump = call_option_bid.bump,
+        constraint = call_option_bid.bidder.is_none() || call_option_bid.bidder == Some(previous_bidder.key()),
+    )]
+    pub call_option_bid: Box<Account<'info, CallOptionBid>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_bid_call_option(ctx: Context<BidCallOption>, amount: u64) -> Result<()> {
+    let call_option = &ctx.accounts.call_option;
+    let call_option_bid = &mut ctx.accounts.call_option_bid;
+
+    if ctx.accounts.clock.unix_timestamp >= call_option.auction_end {
+        return err!(DexloanError::AuctionEnded);
+    }
+
+    if amount < call_option.amount || amount <= call_option_bid.amount {
+        return err!(DexloanError::InvalidAmount);
+    }
+
+    // Refund the outbid buyer
+    if call_option_bid.bidder.is_some() {
+        transfer_from_escrow(
+            &mut call_option_bid.to_account_info(),
+            &mut ctx.accounts.previous_bidder.to_account_info(),
+            call_option_bid.amount,
+        )?;
+    }
+
+    call_option_bid.bidder = Some(ctx.accounts.bidder.key());
+    call_option_bid.amount = amount;
+
+    transfer_currency(
+        &None,
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.bidder.to_account_info(),
+        &ctx.accounts.bidder.to_account_info(),
+        &call_option_bid.to_account_info(),
+        amount,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SettleCallOptionAuction<'info> {
+    pub signer: Signer<'info>,
+    /// CHECK: contrained on call_option
+    #[account(mut)]
+    pub seller: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            CallOption::PREFIX,
+            mint.key().as_ref(),
+            seller.key().as_ref(),
+        ],
+        bump,
+        has_one = seller,
+        has_one = mint,
+        constraint = call_option.state == CallOptionState::Auctioning,
+    )]
+    pub call_option: Box<Account<'info, CallOption>>,
+    #[account(
+        mut,
+        seeds = [
+            CallOptionBid::PREFIX,
+            call_option.key().as_ref(),
+        ],
+        bump = call_option_bid.bump,
+        close = seller,
+    )]
+    pub call_option_bid: Box<Account<'info, CallOptionBid>>,
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// Misc
+    pub clock: Sysvar<'info, Clock>,
+}
+
+// Anyone may settle once bidding has closed
+pub fn handle_settle_call_option_auction(ctx: Context<SettleCallOptionAuction>) -> Result<()> {
+    let call_option = &mut ctx.accounts.call_option;
+    let call_option_bid = &mut ctx.accounts.call_option_bid;
+
+    if ctx.accounts.clock.unix_timestamp < call_option.auction_end {
+        return err!(DexloanError::AuctionNotEnded);
+    }
+
+    match call_option_bid.bidder {
+        Some(bidder) => {
+            let protocol_fee = calculate_fee_from_basis_points(
+                call_option_bid.amount as u128,
+                ctx.accounts.protocol_config.option_premium_fee_basis_points as u128,
+            )?;
+
+            // The remaining premium is paid to the seller when the escrow is closed
+            transfer_from_escrow(
+                &mut call_option_bid.to_account_info(),
+                &mut ctx.accounts.treasury.to_account_info(),
+                protocol_fee,
+            )?;
+
+            call_option.state = CallOptionState::Active;
+            call_option.buyer = bidder;
+            call_option.amount = call_option_bid.amount;
+        }
+        None => {
+            call_option.state = CallOptionState::Listed;
+        }
+    }
+
+    Ok(())
+}
@@ -1,6 +1,9 @@
 use anchor_lang::{prelude::*};
 use anchor_spl::token::{Mint, Token};
-use crate::state::{CallOption, CallOptionState, TokenManager};
+use crate::state::{CallOption, CallOptionState, ProtocolConfig, TokenManager};
+use crate::error::*;
+use crate::utils::*;
+use crate::processor::call_option::CallOptionArgs;
 
 #[derive(Accounts)]
 pub struct BuyCallOption<'info> {
@@ -21,6 +24,7 @@ pub struct BuyCallOption<'info> {
         has_one = seller,
         has_one = mint,
         constraint = call_option.seller != buyer.key(),
+        constraint = call_option.counterparty.is_none() || call_option.counterparty == Some(buyer.key()),
         constraint = call_option.state == CallOptionState::Listed,
     )]
     pub call_option: Box<Account<'info, CallOption>>,   
@@ -34,6 +38,15 @@ pub struct BuyCallOption<'info> {
         bump,
     )]   
     pub token_manager: Box<Account<'info, TokenManager>>, 
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
     pub mint: Box<Account<'info, Mint>>,
     /// CHECK: validated in cpi
     pub edition: UncheckedAccount<'info>,
@@ -45,24 +58,67 @@ pub struct BuyCallOption<'info> {
     pub clock: Sysvar<'info, Clock>,
 }
 
-pub fn handle_buy_call_option(ctx: Context<BuyCallOption>) -> Result<()> {
+pub fn handle_buy_call_option<'info>(ctx: Context<'_, '_, '_, 'info, BuyCallOption<'info>>) -> Result<()> {
     let call_option = &mut ctx.accounts.call_option;
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+
+    if call_option.is_listing_expired(ctx.accounts.clock.unix_timestamp) {
+        return err!(DexloanError::ListingExpired);
+    }
 
     call_option.state = CallOptionState::Active;
     call_option.buyer = ctx.accounts.buyer.key();
 
+    let protocol_fee = calculate_fee_from_basis_points(
+        call_option.amount as u128,
+        ctx.accounts.protocol_config.option_premium_fee_basis_points as u128,
+    )?;
+
+    let buyer_currency_account = next_currency_account(
+        &call_option.currency_mint,
+        &ctx.accounts.buyer.to_account_info(),
+        remaining_accounts,
+    )?;
+    let seller_currency_account = next_currency_account(
+        &call_option.currency_mint,
+        &ctx.accounts.seller.to_account_info(),
+        remaining_accounts,
+    )?;
+    let treasury_currency_account = next_currency_account(
+        &call_option.currency_mint,
+        &ctx.accounts.treasury.to_account_info(),
+        remaining_accounts,
+    )?;
+
     // Transfer option cost
-    anchor_lang::solana_program::program::invoke(
-        &anchor_lang::solana_program::system_instruction::transfer(
-            &call_option.buyer,
-            &call_option.seller,
-            call_option.amount,
-        ),
-        &[
-            ctx.accounts.seller.to_account_info(),
-            ctx.accounts.buyer.to_account_info(),
-        ]
+    transfer_currency(
+        &call_option.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &buyer_currency_account,
+        &seller_currency_account,
+        call_option.amount - protocol_fee,
+    )?;
+    transfer_currency(
+        &call_option.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &buyer_currency_account,
+        &treasury_currency_account,
+        protocol_fee,
     )?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+// Guards against the listing's terms being changed before the transaction lands
+pub fn handle_buy_call_option_with_terms<'info>(
+    ctx: Context<'_, '_, '_, 'info, BuyCallOption<'info>>,
+    expected_terms: CallOptionArgs,
+) -> Result<()> {
+    if !expected_terms.matches(&ctx.accounts.call_option) {
+        return err!(DexloanError::TermsMismatch);
+    }
+
+    handle_buy_call_option(ctx)
+}
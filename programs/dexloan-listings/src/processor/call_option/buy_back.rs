@@ -0,0 +1,190 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::state::{CallOption, CallOptionState, ProtocolConfig, TokenManager};
+use crate::error::*;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct ListCallOptionBuyBack<'info> {
+    /// CHECK: contrained on call_option
+    pub seller: AccountInfo<'info>,
+    pub buyer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            CallOption::PREFIX,
+            mint.key().as_ref(),
+            seller.key().as_ref(),
+        ],
+        bump,
+        has_one = seller,
+        has_one = buyer,
+        has_one = mint,
+        constraint = call_option.state == CallOptionState::Active,
+    )]
+    pub call_option: Box<Account<'info, CallOption>>,
+    pub mint: Box<Account<'info, Mint>>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+// Passing no price withdraws the offer
+pub fn handle_list_call_option_buy_back(ctx: Context<ListCallOptionBuyBack>, price: Option<u64>) -> Result<()> {
+    let call_option = &mut ctx.accounts.call_option;
+
+    if ctx.accounts.clock.unix_timestamp > call_option.expiry {
+        return err!(DexloanError::OptionExpired);
+    }
+
+    if price == Some(0) {
+        return err!(DexloanError::InvalidAmount);
+    }
+
+    call_option.buy_back_price = price;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct BuyBackCallOption<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    /// CHECK: contrained on call_option
+    #[account(mut)]
+    pub buyer: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            CallOption::PREFIX,
+            mint.key().as_ref(),
+            seller.key().as_ref(),
+        ],
+        bump,
+        has_one = seller,
+        has_one = buyer,
+        has_one = mint,
+        constraint = call_option.state == CallOptionState::Active,
+        constraint = call_option.buy_back_price.is_some(),
+        close = seller
+    )]
+    pub call_option: Box<Account<'info, CallOption>>,
+    #[account(
+        mut,
+        seeds = [
+            TokenManager::PREFIX,
+            mint.key().as_ref(),
+            seller.key().as_ref()
+        ],
+        bump,
+    )]
+    pub token_manager: Box<Account<'info, TokenManager>>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = seller
+    )]
+    pub deposit_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: validated in cpi
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub metadata_program: UncheckedAccount<'info>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_buy_back_call_option<'info>(
+    ctx: Context<'_, '_, '_, 'info, BuyBackCallOption<'info>>,
+    expected_price: u64,
+) -> Result<()> {
+    let call_option = &ctx.accounts.call_option;
+    let token_manager = &mut ctx.accounts.token_manager;
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+
+    if ctx.accounts.clock.unix_timestamp > call_option.expiry {
+        return err!(DexloanError::OptionExpired);
+    }
+
+    let price = call_option.buy_back_price.unwrap();
+
+    // Guards against the asking price being changed before the transaction lands
+    if price != expected_price {
+        return err!(DexloanError::TermsMismatch);
+    }
+    let protocol_fee = calculate_fee_from_basis_points(
+        price as u128,
+        ctx.accounts.protocol_config.option_premium_fee_basis_points as u128,
+    )?;
+
+    let seller_currency_account = next_currency_account(
+        &call_option.currency_mint,
+        &ctx.accounts.seller.to_account_info(),
+        remaining_accounts,
+    )?;
+    let buyer_currency_account = next_currency_account(
+        &call_option.currency_mint,
+        &ctx.accounts.buyer.to_account_info(),
+        remaining_accounts,
+    )?;
+    let treasury_currency_account = next_currency_account(
+        &call_option.currency_mint,
+        &ctx.accounts.treasury.to_account_info(),
+        remaining_accounts,
+    )?;
+
+    transfer_currency(
+        &call_option.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.seller.to_account_info(),
+        &seller_currency_account,
+        &buyer_currency_account,
+        price - protocol_fee,
+    )?;
+    transfer_currency(
+        &call_option.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.seller.to_account_info(),
+        &seller_currency_account,
+        &treasury_currency_account,
+        protocol_fee,
+    )?;
+
+    token_manager.accounts.call_option = false;
+    // The NFT stays frozen for any other contract
+    if token_manager.accounts.hire || token_manager.accounts.loan || token_manager.bundle {
+        return Ok(());
+    }
+
+    if ctx.accounts.deposit_token_account.is_frozen() {
+        thaw_and_revoke_token_account(
+            token_manager,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.deposit_token_account.to_account_info(),
+            ctx.accounts.seller.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.edition.to_account_info()
+        )?;
+    } else {
+        anchor_spl::token::revoke(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::Revoke {
+                    source: ctx.accounts.deposit_token_account.to_account_info(),
+                    authority: ctx.accounts.seller.to_account_info(),
+                }
+            )
+        )?;
+    }
+
+    Ok(())
+}
@@ -20,6 +20,7 @@ pub struct CloseCallOption<'info> {
         bump,
         has_one = seller,
         has_one = mint,
+        constraint = call_option.state != CallOptionState::Auctioning,
         close = seller
     )]
     pub call_option: Account<'info, CallOption>,
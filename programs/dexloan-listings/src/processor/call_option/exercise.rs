@@ -2,7 +2,7 @@ use anchor_lang::{
   prelude::*,
 };
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use crate::state::{CallOption, CallOptionState, Hire, TokenManager};
+use crate::state::{CallOption, CallOptionState, Hire, ProtocolConfig, TokenManager};
 use crate::error::{DexloanError};
 use crate::utils::*;
 
@@ -50,6 +50,15 @@ pub struct ExerciseCallOption<'info> {
         associated_token::authority = seller
     )]
     pub deposit_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
     pub mint: Box<Account<'info, Mint>>,
     /// CHECK: validated in cpi
     pub edition: UncheckedAccount<'info>,
@@ -77,6 +86,10 @@ pub fn handle_exercise_call_option<'info>(ctx: Context<'_, '_, '_, 'info, Exerci
         return Err(DexloanError::OptionExpired.into())
     }
 
+    if !call_option.is_exercisable(unix_timestamp) {
+        return Err(DexloanError::OptionNotExercisable.into())
+    }
+
     call_option.state = CallOptionState::Exercised;
     token_manager.accounts.call_option = false;
     token_manager.accounts.hire = false;
@@ -91,27 +104,58 @@ pub fn handle_exercise_call_option<'info>(ctx: Context<'_, '_, '_, 'info, Exerci
         ctx.accounts.edition.to_account_info(),
     )?;
 
+    let buyer_currency_account = next_currency_account(
+        &call_option.currency_mint,
+        &ctx.accounts.buyer.to_account_info(),
+        remaining_accounts,
+    )?;
+    let seller_currency_account = next_currency_account(
+        &call_option.currency_mint,
+        &ctx.accounts.seller.to_account_info(),
+        remaining_accounts,
+    )?;
+    let treasury_currency_account = next_currency_account(
+        &call_option.currency_mint,
+        &ctx.accounts.treasury.to_account_info(),
+        remaining_accounts,
+    )?;
+
     let remaining_amount = pay_creator_fees(
         remaining_accounts,
         call_option.strike_price,
         &ctx.accounts.mint.to_account_info(),
         &ctx.accounts.metadata.to_account_info(),
+        &call_option.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
         &ctx.accounts.buyer.to_account_info(),
+        &buyer_currency_account,
     )?;  
 
     msg!("remaining_amount {}", remaining_amount);
     msg!("paid to creators {}", call_option.strike_price - remaining_amount);
 
-    anchor_lang::solana_program::program::invoke(
-        &anchor_lang::solana_program::system_instruction::transfer(
-            &call_option.buyer,
-            &call_option.seller,
-            remaining_amount,
-        ),
-        &[
-            ctx.accounts.buyer.to_account_info(),
-            ctx.accounts.seller.to_account_info(),
-        ]
+    let protocol_fee = calculate_fee_from_basis_points(
+        call_option.strike_price as u128,
+        ctx.accounts.protocol_config.option_exercise_fee_basis_points as u128,
+    )?;
+
+    transfer_currency(
+        &call_option.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &buyer_currency_account,
+        &seller_currency_account,
+        remaining_amount
+            .checked_sub(protocol_fee)
+            .ok_or(DexloanError::NumericalOverflow)?,
+    )?;
+    transfer_currency(
+        &call_option.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &buyer_currency_account,
+        &treasury_currency_account,
+        protocol_fee,
     )?;
   
     Ok(())
@@ -185,6 +229,15 @@ pub struct ExerciseCallOptionWithHire<'info> {
         associated_token::authority = buyer
     )]
     pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
     pub mint: Box<Account<'info, Mint>>,
     /// CHECK: validated in cpi
     pub edition: UncheckedAccount<'info>,
@@ -212,6 +265,10 @@ pub fn handle_exercise_call_option_with_hire<'info>(ctx: Context<'_, '_, '_, 'in
         return Err(DexloanError::OptionExpired.into())
     }
 
+    if !call_option.is_exercisable(unix_timestamp) {
+        return Err(DexloanError::OptionNotExercisable.into())
+    }
+
     call_option.state = CallOptionState::Exercised;
     token_manager.accounts.call_option = false;
     token_manager.accounts.hire = false;
@@ -226,24 +283,55 @@ pub fn handle_exercise_call_option_with_hire<'info>(ctx: Context<'_, '_, '_, 'in
         ctx.accounts.edition.to_account_info(),
     )?;
 
+    let buyer_currency_account = next_currency_account(
+        &call_option.currency_mint,
+        &ctx.accounts.buyer.to_account_info(),
+        remaining_accounts,
+    )?;
+    let seller_currency_account = next_currency_account(
+        &call_option.currency_mint,
+        &ctx.accounts.seller.to_account_info(),
+        remaining_accounts,
+    )?;
+    let treasury_currency_account = next_currency_account(
+        &call_option.currency_mint,
+        &ctx.accounts.treasury.to_account_info(),
+        remaining_accounts,
+    )?;
+
     let remaining_amount = pay_creator_fees(
         remaining_accounts,
         call_option.strike_price,
         &ctx.accounts.mint.to_account_info(),
         &ctx.accounts.metadata.to_account_info(),
+        &call_option.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
         &ctx.accounts.buyer.to_account_info(),
+        &buyer_currency_account,
     )?;
 
-    anchor_lang::solana_program::program::invoke(
-        &anchor_lang::solana_program::system_instruction::transfer(
-            &call_option.buyer,
-            &call_option.seller,
-            remaining_amount,
-        ),
-        &[
-            ctx.accounts.buyer.to_account_info(),
-            ctx.accounts.seller.to_account_info(),
-        ]
+    let protocol_fee = calculate_fee_from_basis_points(
+        call_option.strike_price as u128,
+        ctx.accounts.protocol_config.option_exercise_fee_basis_points as u128,
+    )?;
+
+    transfer_currency(
+        &call_option.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &buyer_currency_account,
+        &seller_currency_account,
+        remaining_amount
+            .checked_sub(protocol_fee)
+            .ok_or(DexloanError::NumericalOverflow)?,
+    )?;
+    transfer_currency(
+        &call_option.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &buyer_currency_account,
+        &treasury_currency_account,
+        protocol_fee,
     )?;
 
     if hire.borrower.is_some() {
@@ -252,6 +340,8 @@ pub fn handle_exercise_call_option_with_hire<'info>(ctx: Context<'_, '_, '_, 'in
             remaining_accounts,
             &ctx.accounts.hire_escrow.to_account_info(),
             &ctx.accounts.seller.to_account_info(),
+            &ctx.accounts.protocol_config,
+            &ctx.accounts.treasury.to_account_info(),
             unix_timestamp,
         )?;
     }
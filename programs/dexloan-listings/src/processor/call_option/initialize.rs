@@ -1,11 +1,41 @@
 use anchor_lang::{prelude::*};
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use crate::state::{CallOption, CallOptionState, Hire, HireState, TokenManager};
+use crate::state::{CallOption, CallOptionState, ExerciseStyle, Hire, HireState, TokenManager};
 use crate::error::{DexloanError};
 use crate::utils::*;
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CallOptionArgs {
+    pub amount: u64,
+    pub strike_price: u64,
+    pub expiry: i64,
+    pub listing_expiry: Option<i64>,
+    pub counterparty: Option<Pubkey>,
+    pub exercise_style: ExerciseStyle,
+    pub currency_mint: Option<Pubkey>,
+}
+
+impl CallOptionArgs {
+    pub fn validate(&self, unix_timestamp: i64) -> Result<()> {
+        if unix_timestamp > self.expiry {
+            return err!(DexloanError::InvalidExpiry);
+        }
+
+        self.exercise_style.validate(self.expiry)
+    }
+
+    /// Whether the call option's economic terms are those given
+    pub fn matches(&self, call_option: &CallOption) -> bool {
+        call_option.amount == self.amount &&
+        call_option.strike_price == self.strike_price &&
+        call_option.expiry == self.expiry &&
+        call_option.exercise_style == self.exercise_style &&
+        call_option.currency_mint == self.currency_mint
+    }
+}
+
 #[derive(Accounts)]
-#[instruction(amount: u64, strike_price: u64, expiry: i64)]
+#[instruction(args: CallOptionArgs)]
 pub struct InitCallOption<'info> {
     #[account(mut)]
     pub seller: Signer<'info>,
@@ -56,37 +86,40 @@ pub struct InitCallOption<'info> {
 
 pub fn handle_init_call_option(
   ctx: Context<InitCallOption>,
-  amount: u64,
-  strike_price: u64,
-  expiry: i64
+  args: CallOptionArgs
 ) -> Result<()> {
     let call_option = &mut ctx.accounts.call_option;
     let token_manager = &mut ctx.accounts.token_manager;
     let deposit_token_account = &mut ctx.accounts.deposit_token_account;
     let unix_timestamp = ctx.accounts.clock.unix_timestamp;
 
-    if unix_timestamp > expiry {
-        return Err(DexloanError::InvalidExpiry.into())
-    }
+    args.validate(unix_timestamp)?;
 
     require_eq!(token_manager.accounts.hire, false, DexloanError::InvalidState);
     require_eq!(token_manager.accounts.call_option, false, DexloanError::InvalidState);
+    require_eq!(token_manager.bundle, false, DexloanError::InvalidState);
 
     // Init
     call_option.seller = ctx.accounts.seller.key();
     call_option.mint = ctx.accounts.mint.key();
     call_option.bump = *ctx.bumps.get("call_option").unwrap();
     //
-    call_option.amount = amount;
-    call_option.expiry = expiry;
-    call_option.strike_price = strike_price;
+    call_option.amount = args.amount;
+    call_option.expiry = args.expiry;
+    call_option.strike_price = args.strike_price;
+    call_option.listing_expiry = args.listing_expiry;
+    call_option.counterparty = args.counterparty;
+    call_option.exercise_style = args.exercise_style;
+    call_option.currency_mint = args.currency_mint;
     call_option.state = CallOptionState::Listed;
     //
     token_manager.accounts.call_option = true;
     token_manager.bump = *ctx.bumps.get("token_manager").unwrap();
 
     if deposit_token_account.delegate.is_some() {
-        if !deposit_token_account.is_frozen() && deposit_token_account.delegate.unwrap() != token_manager.key()  {
+        // An unfrozen token is secured again whoever the delegate is, as a
+        // crank may have thawed it while leaving the delegation in place
+        if !deposit_token_account.is_frozen() {
             anchor_spl::token::revoke(
                 CpiContext::new(
                     ctx.accounts.token_program.to_account_info(),
@@ -125,7 +158,7 @@ pub fn handle_init_call_option(
 }
 
 #[derive(Accounts)]
-#[instruction(amount: u64, strike_price: u64, expiry: i64)]
+#[instruction(args: CallOptionArgs)]
 pub struct InitCallOptionWithHire<'info> {
     #[account(mut)]
     pub seller: Signer<'info>,
@@ -190,9 +223,7 @@ pub struct InitCallOptionWithHire<'info> {
 
 pub fn handle_init_call_option_with_hire(
     ctx: Context<InitCallOptionWithHire>,
-    amount: u64,
-    strike_price: u64,
-    expiry: i64
+    args: CallOptionArgs
   ) -> Result<()> {
     let call_option = &mut ctx.accounts.call_option;
     let token_manager = &mut ctx.accounts.token_manager;
@@ -200,19 +231,22 @@ pub fn handle_init_call_option_with_hire(
 
     require_eq!(token_manager.accounts.hire, true, DexloanError::InvalidState);
     require_eq!(token_manager.accounts.call_option, false, DexloanError::InvalidState);
+    require_eq!(token_manager.bundle, false, DexloanError::InvalidState);
 
-    if unix_timestamp > expiry {
-        return Err(DexloanError::InvalidExpiry.into())
-    }
+    args.validate(unix_timestamp)?;
 
     // Init
     call_option.seller = ctx.accounts.seller.key();
     call_option.mint = ctx.accounts.mint.key();
     call_option.bump = *ctx.bumps.get("call_option").unwrap();
     //
-    call_option.amount = amount;
-    call_option.expiry = expiry;
-    call_option.strike_price = strike_price;
+    call_option.amount = args.amount;
+    call_option.expiry = args.expiry;
+    call_option.strike_price = args.strike_price;
+    call_option.listing_expiry = args.listing_expiry;
+    call_option.counterparty = args.counterparty;
+    call_option.exercise_style = args.exercise_style;
+    call_option.currency_mint = args.currency_mint;
     call_option.state = CallOptionState::Listed;
     //
     token_manager.accounts.call_option = true;
@@ -0,0 +1,26 @@
+use anchor_lang::{prelude::*, Discriminator};
+use crate::state::{CallOption};
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct MigrateCallOption<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: can't be deserialized until migrated, checked in handler
+    #[account(mut, owner = crate::ID)]
+    pub call_option: UncheckedAccount<'info>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+}
+
+// Anyone may pay to extend a call option created before the current layout
+pub fn handle_migrate_call_option(ctx: Context<MigrateCallOption>) -> Result<()> {
+    migrate_account(
+        &ctx.accounts.call_option.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        CallOption::discriminator(),
+        CallOption::legacy_space(),
+        CallOption::space(),
+    )
+}
@@ -1,9 +1,19 @@
+pub mod auction;
 pub mod buy;
+pub mod buy_back;
 pub mod close;
 pub mod exercise;
 pub mod initialize;
+pub mod migrate;
+pub mod position;
+pub mod update;
 
+pub use auction::*;
 pub use buy::*;
+pub use buy_back::*;
 pub use close::*;
 pub use exercise::*;
-pub use initialize::*;
\ No newline at end of file
+pub use initialize::*;
+pub use migrate::*;
+pub use position::*;
+pub use update::*;
\ No newline at end of file
@@ -0,0 +1,149 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Mint, Token};
+use crate::state::{CallOption, CallOptionState, ProtocolConfig};
+use crate::error::*;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct ListCallOptionPosition<'info> {
+    /// CHECK: contrained on call_option
+    pub seller: AccountInfo<'info>,
+    pub buyer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            CallOption::PREFIX,
+            mint.key().as_ref(),
+            seller.key().as_ref(),
+        ],
+        bump,
+        has_one = seller,
+        has_one = buyer,
+        has_one = mint,
+        constraint = call_option.state == CallOptionState::Active,
+    )]
+    pub call_option: Box<Account<'info, CallOption>>,
+    pub mint: Box<Account<'info, Mint>>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+// Passing no price delists the position
+pub fn handle_list_call_option_position(ctx: Context<ListCallOptionPosition>, price: Option<u64>) -> Result<()> {
+    let call_option = &mut ctx.accounts.call_option;
+
+    if ctx.accounts.clock.unix_timestamp > call_option.expiry {
+        return err!(DexloanError::OptionExpired);
+    }
+
+    if price == Some(0) {
+        return err!(DexloanError::InvalidAmount);
+    }
+
+    call_option.position_price = price;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct BuyCallOptionPosition<'info> {
+    /// CHECK: contrained on call_option
+    pub seller: AccountInfo<'info>,
+    /// CHECK: contrained on call_option
+    #[account(mut)]
+    pub buyer: AccountInfo<'info>,
+    #[account(mut)]
+    pub new_buyer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            CallOption::PREFIX,
+            mint.key().as_ref(),
+            seller.key().as_ref(),
+        ],
+        bump,
+        has_one = seller,
+        has_one = buyer,
+        has_one = mint,
+        constraint = call_option.buyer != new_buyer.key(),
+        constraint = call_option.state == CallOptionState::Active,
+        constraint = call_option.position_price.is_some(),
+    )]
+    pub call_option: Box<Account<'info, CallOption>>,
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_buy_call_option_position<'info>(
+    ctx: Context<'_, '_, '_, 'info, BuyCallOptionPosition<'info>>,
+    expected_price: u64,
+) -> Result<()> {
+    let call_option = &mut ctx.accounts.call_option;
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+
+    if ctx.accounts.clock.unix_timestamp > call_option.expiry {
+        return err!(DexloanError::OptionExpired);
+    }
+
+    let price = call_option.position_price.unwrap();
+
+    // Guards against the asking price being changed before the transaction lands
+    if price != expected_price {
+        return err!(DexloanError::TermsMismatch);
+    }
+    let protocol_fee = calculate_fee_from_basis_points(
+        price as u128,
+        ctx.accounts.protocol_config.option_premium_fee_basis_points as u128,
+    )?;
+
+    call_option.buyer = ctx.accounts.new_buyer.key();
+    call_option.position_price = None;
+    // Offers made by the previous holder no longer apply
+    call_option.buy_back_price = None;
+
+    let new_buyer_currency_account = next_currency_account(
+        &call_option.currency_mint,
+        &ctx.accounts.new_buyer.to_account_info(),
+        remaining_accounts,
+    )?;
+    let buyer_currency_account = next_currency_account(
+        &call_option.currency_mint,
+        &ctx.accounts.buyer.to_account_info(),
+        remaining_accounts,
+    )?;
+    let treasury_currency_account = next_currency_account(
+        &call_option.currency_mint,
+        &ctx.accounts.treasury.to_account_info(),
+        remaining_accounts,
+    )?;
+
+    transfer_currency(
+        &call_option.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.new_buyer.to_account_info(),
+        &new_buyer_currency_account,
+        &buyer_currency_account,
+        price - protocol_fee,
+    )?;
+    transfer_currency(
+        &call_option.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.new_buyer.to_account_info(),
+        &new_buyer_currency_account,
+        &treasury_currency_account,
+        protocol_fee,
+    )?;
+
+    Ok(())
+}
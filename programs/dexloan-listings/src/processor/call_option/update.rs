@@ -0,0 +1,47 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Mint};
+use crate::state::{CallOption, CallOptionState};
+use crate::processor::call_option::CallOptionArgs;
+
+#[derive(Accounts)]
+#[instruction(args: CallOptionArgs)]
+pub struct UpdateCallOption<'info> {
+    pub seller: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            CallOption::PREFIX,
+            mint.key().as_ref(),
+            seller.key().as_ref(),
+        ],
+        bump,
+        has_one = seller,
+        has_one = mint,
+        constraint = call_option.state == CallOptionState::Listed,
+    )]
+    pub call_option: Box<Account<'info, CallOption>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// Misc
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_update_call_option(
+  ctx: Context<UpdateCallOption>,
+  args: CallOptionArgs,
+) -> Result<()> {
+    let call_option = &mut ctx.accounts.call_option;
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    args.validate(unix_timestamp)?;
+
+    // The deposit remains frozen under the token manager
+    call_option.amount = args.amount;
+    call_option.expiry = args.expiry;
+    call_option.strike_price = args.strike_price;
+    call_option.listing_expiry = args.listing_expiry;
+    call_option.counterparty = args.counterparty;
+    call_option.exercise_style = args.exercise_style;
+    call_option.currency_mint = args.currency_mint;
+
+    Ok(())
+}
@@ -6,9 +6,9 @@ use crate::utils::*;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct HireArgs {
-    amount: u64,
-    expiry: i64,
-    borrower: Option<Pubkey>,
+    pub amount: u64,
+    pub expiry: i64,
+    pub borrower: Option<Pubkey>,
 }
 
 #[derive(Accounts)]
@@ -75,6 +75,8 @@ pub fn handle_init_hire(
         return err!(DexloanError::BorrowerNotSpecified)
     }
 
+    require_eq!(token_manager.bundle, false, DexloanError::InvalidState);
+
     // Init
     hire.lender = ctx.accounts.lender.key();
     hire.mint = ctx.accounts.mint.key();
@@ -92,7 +94,9 @@ pub fn handle_init_hire(
     token_manager.bump = *ctx.bumps.get("token_manager").unwrap();
 
     if deposit_token_account.delegate.is_some() {
-        if !deposit_token_account.is_frozen() && deposit_token_account.delegate.unwrap() != token_manager.key()  {
+        // An unfrozen token is secured again whoever the delegate is, as a
+        // crank may have thawed it while leaving the delegation in place
+        if !deposit_token_account.is_frozen() {
             anchor_spl::token::revoke(
                 CpiContext::new(
                     ctx.accounts.token_program.to_account_info(),
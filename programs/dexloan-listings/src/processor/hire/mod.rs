@@ -3,6 +3,7 @@ pub mod extend;
 pub mod take;
 pub mod initialize;
 pub mod recover;
+pub mod update;
 pub mod withdraw;
 
 pub use close::*;
@@ -10,4 +11,5 @@ pub use extend::*;
 pub use take::*;
 pub use initialize::*;
 pub use recover::*;
+pub use update::*;
 pub use withdraw::*;
@@ -1,6 +1,6 @@
 use anchor_lang::{prelude::*};
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use crate::state::{Hire, HireState, TokenManager};
+use crate::state::{Hire, HireState, ProtocolConfig, TokenManager};
 use crate::error::{DexloanError};
 use crate::utils::*;
 
@@ -58,6 +58,15 @@ pub struct RecoverHire<'info> {
         bump,
     )] 
     pub token_manager: Box<Account<'info, TokenManager>>,    
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
     #[account(constraint = mint.supply == 1)]
     pub mint: Box<Account<'info, Mint>>,
     /// CHECK: validated in cpi
@@ -83,6 +92,8 @@ pub fn handle_recover_hire(ctx: Context<RecoverHire>) -> Result<()> {
             hire,
             &ctx.accounts.hire_escrow.to_account_info(),
             &ctx.accounts.lender.to_account_info(),
+            &ctx.accounts.protocol_config,
+            &ctx.accounts.treasury.to_account_info(),
             unix_timestamp,
         )?;
     }
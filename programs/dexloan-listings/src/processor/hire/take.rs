@@ -1,6 +1,6 @@
 use anchor_lang::{prelude::*};
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use crate::state::{Hire, HireState, TokenManager};
+use crate::state::{Hire, HireState, ProtocolConfig, TokenManager};
 use crate::error::{DexloanError};
 use crate::constants::*;
 use crate::utils::*;
@@ -60,6 +60,15 @@ pub struct TakeHire <'info> {
         bump,
     )]   
     pub token_manager: Box<Account<'info, TokenManager>>,  
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
     #[account(constraint = mint.supply == 1)]
     pub mint: Box<Account<'info, Mint>>,
     /// CHECK: validated in cpi
@@ -86,6 +95,8 @@ pub fn handle_take_hire<'info>(ctx: Context<'_, '_, '_, 'info, TakeHire<'info>>,
             hire,
             &ctx.accounts.hire_escrow.to_account_info(),
             &ctx.accounts.lender.to_account_info(),
+            &ctx.accounts.protocol_config,
+            &ctx.accounts.treasury.to_account_info(),
             unix_timestamp,
         )?;
     }
@@ -137,4 +148,21 @@ pub fn handle_take_hire<'info>(ctx: Context<'_, '_, '_, 'info, TakeHire<'info>>,
     )?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+// Guards against the listing's daily rate being raised or its expiry being
+// changed before the transaction lands
+pub fn handle_take_hire_with_terms<'info>(
+    ctx: Context<'_, '_, '_, 'info, TakeHire<'info>>,
+    days: u16,
+    max_daily_rate: u64,
+    expected_expiry: i64,
+) -> Result<()> {
+    let hire = &ctx.accounts.hire;
+
+    if hire.amount > max_daily_rate || hire.expiry != expected_expiry {
+        return err!(DexloanError::TermsMismatch);
+    }
+
+    handle_take_hire(ctx, days)
+}
@@ -0,0 +1,49 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Mint};
+use crate::state::{Hire, HireState};
+use crate::error::{DexloanError};
+use crate::processor::hire::HireArgs;
+
+#[derive(Accounts)]
+pub struct UpdateHire<'info> {
+    pub lender: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Hire::PREFIX,
+            mint.key().as_ref(),
+            lender.key().as_ref(),
+        ],
+        bump,
+        has_one = lender,
+        has_one = mint,
+        constraint = hire.state == HireState::Listed,
+    )]
+    pub hire: Box<Account<'info, Hire>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// Misc
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_update_hire(
+  ctx: Context<UpdateHire>,
+  args: HireArgs,
+) -> Result<()> {
+    let hire = &mut ctx.accounts.hire;
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    if unix_timestamp > args.expiry {
+        return err!(DexloanError::InvalidExpiry)
+    }
+
+    if args.amount == 0 && args.borrower.is_none() {
+        return err!(DexloanError::BorrowerNotSpecified)
+    }
+
+    // The deposit remains frozen under the token manager
+    hire.amount = args.amount;
+    hire.expiry = args.expiry;
+    hire.borrower = args.borrower;
+
+    Ok(())
+}
\ No newline at end of file
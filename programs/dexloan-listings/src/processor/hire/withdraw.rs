@@ -1,6 +1,6 @@
 use anchor_lang::{prelude::*};
 use anchor_spl::token::{Mint, Token};
-use crate::state::{Hire};
+use crate::state::{Hire, ProtocolConfig};
 use crate::utils::*;
 
 #[derive(Accounts)]
@@ -32,6 +32,15 @@ pub struct WithdrawFromHireEscrow<'info> {
         bump,
     )]
     pub hire_escrow: AccountInfo<'info>,  
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
     pub mint: Box<Account<'info, Mint>>,
     /// Misc
     pub system_program: Program<'info, System>,
@@ -47,6 +56,8 @@ pub fn handle_withdraw_from_hire_escrow(ctx: Context<WithdrawFromHireEscrow>) ->
         hire,
         &ctx.accounts.hire_escrow.to_account_info(),
         &ctx.accounts.lender.to_account_info(),
+        &ctx.accounts.protocol_config,
+        &ctx.accounts.treasury.to_account_info(),
         ctx.accounts.clock.unix_timestamp,
     )?;
 
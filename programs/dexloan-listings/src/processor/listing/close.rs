@@ -0,0 +1,104 @@
+use anchor_lang::{prelude::*, AccountsClose};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::state::{CallOption, CallOptionState, Loan, LoanState, TokenManager};
+use crate::error::*;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct CloseExpiredListing<'info> {
+    pub signer: Signer<'info>,
+    /// CHECK: validated against the listing
+    #[account(mut)]
+    pub creator: AccountInfo<'info>,
+    /// CHECK: deserialized as either a loan or a call option
+    #[account(mut)]
+    pub listing: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = deposit_token_account.owner == creator.key(),
+        constraint = deposit_token_account.mint == mint.key(),
+    )]
+    pub deposit_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [
+            TokenManager::PREFIX,
+            mint.key().as_ref(),
+            creator.key().as_ref()
+        ],
+        bump,
+    )]
+    pub token_manager: Box<Account<'info, TokenManager>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: validated in cpi
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub metadata_program: UncheckedAccount<'info>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_close_expired_listing<'info>(ctx: Context<'_, '_, '_, 'info, CloseExpiredListing<'info>>) -> Result<()> {
+    let token_manager = &mut ctx.accounts.token_manager;
+    let listing = &ctx.accounts.listing;
+    let creator = ctx.accounts.creator.to_account_info();
+    let mint = ctx.accounts.mint.key();
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    if let Ok(loan) = Account::<Loan>::try_from(listing) {
+        if loan.mint != mint || loan.borrower != creator.key() || loan.state != LoanState::Listed {
+            return err!(DexloanError::InvalidState);
+        }
+
+        if !loan.is_listing_expired(unix_timestamp) {
+            return err!(DexloanError::ListingNotExpired);
+        }
+
+        token_manager.accounts.loan = false;
+        loan.close(creator.clone())?;
+    } else {
+        let call_option = Account::<CallOption>::try_from(listing)?;
+
+        if call_option.mint != mint || call_option.seller != creator.key() || call_option.state != CallOptionState::Listed {
+            return err!(DexloanError::InvalidState);
+        }
+
+        if !call_option.is_listing_expired(unix_timestamp) {
+            return err!(DexloanError::ListingNotExpired);
+        }
+
+        token_manager.accounts.call_option = false;
+        call_option.close(creator.clone())?;
+    }
+
+    if token_manager.accounts.loan || token_manager.accounts.call_option || token_manager.accounts.hire || token_manager.bundle {
+        return Ok(());
+    }
+
+    if ctx.accounts.deposit_token_account.is_frozen() {
+        // Only the owner can revoke the delegation, so the token is just
+        // thawed unless the creator has signed
+        if creator.is_signer {
+            thaw_and_revoke_token_account(
+                token_manager,
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.deposit_token_account.to_account_info(),
+                creator,
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.edition.to_account_info(),
+            )?;
+        } else {
+            thaw_token_account(
+                token_manager,
+                ctx.accounts.deposit_token_account.to_account_info(),
+                creator,
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.edition.to_account_info(),
+            )?;
+        }
+    }
+
+    Ok(())
+}
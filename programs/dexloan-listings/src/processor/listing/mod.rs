@@ -0,0 +1,3 @@
+pub mod close;
+
+pub use close::*;
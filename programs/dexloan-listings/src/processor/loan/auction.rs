@@ -0,0 +1,163 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+use crate::state::{Loan, LoanState, ProtocolConfig, TokenManager};
+use crate::error::{DexloanError};
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct BuyLoanCollateral<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    /// CHECK: contrained on loan_account
+    #[account(mut)]
+    pub borrower: AccountInfo<'info>,
+    /// CHECK: contrained on loan_account
+    #[account(mut)]
+    pub lender: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = borrower,
+    )]
+    pub deposit_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        bump,
+        has_one = borrower,
+        constraint = loan.note_mint.is_some() || loan.lender == lender.key(),
+        has_one = mint,
+        constraint = loan.state == LoanState::Auctioning,
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        mut,
+        seeds = [
+            TokenManager::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref()
+        ],
+        bump,
+        constraint = !token_manager.accounts.hire,
+    )]
+    pub token_manager: Box<Account<'info, TokenManager>>,
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: validated in cpi
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: deserialized and checked
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub metadata_program: UncheckedAccount<'info>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_buy_loan_collateral<'info>(ctx: Context<'_, '_, '_, 'info, BuyLoanCollateral<'info>>) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let token_manager = &mut ctx.accounts.token_manager;
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    assert_lender_position(loan, &ctx.accounts.lender.to_account_info(), remaining_accounts)?;
+
+    if unix_timestamp >= calculate_auction_end(loan)? {
+        return Err(DexloanError::AuctionEnded.into())
+    }
+
+    let price = calculate_auction_price(loan, unix_timestamp)?;
+
+    msg!("Buying collateral for {} lamports", price);
+
+    let remaining_amount = pay_creator_fees(
+        remaining_accounts,
+        price,
+        &ctx.accounts.mint.to_account_info(),
+        &ctx.accounts.metadata.to_account_info(),
+        &None,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+    )?;
+
+    // Interest stops accruing once the auction has started
+    let amount_due = calculate_loan_amount_due(loan, loan.auction_start)?;
+    let lender_amount = remaining_amount.min(amount_due);
+    let surplus = remaining_amount - lender_amount;
+
+    // The protocol fee is only taken from interest
+    let protocol_fee = calculate_fee_from_basis_points(
+        lender_amount.saturating_sub(loan.amount) as u128,
+        ctx.accounts.protocol_config.loan_fee_basis_points as u128,
+    )?;
+
+    msg!("Paying {} to lender and {} to borrower", lender_amount, surplus);
+
+    // Auction loans are always denominated in lamports
+
+    transfer_currency(
+        &None,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.lender.to_account_info(),
+        lender_amount - protocol_fee,
+    )?;
+    transfer_currency(
+        &None,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.treasury.to_account_info(),
+        protocol_fee,
+    )?;
+    transfer_currency(
+        &None,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.borrower.to_account_info(),
+        surplus,
+    )?;
+
+    loan.state = LoanState::Defaulted;
+    token_manager.accounts.loan = false;
+
+    thaw_and_transfer_from_token_account(
+        token_manager,
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.borrower.to_account_info(),
+        ctx.accounts.deposit_token_account.to_account_info(),
+        ctx.accounts.buyer_token_account.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.edition.to_account_info(),
+    )?;
+
+    Ok(())
+}
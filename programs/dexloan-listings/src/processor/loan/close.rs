@@ -21,7 +21,7 @@ pub struct CloseLoan<'info> {
         bump = loan.bump,
         constraint = loan.borrower == *borrower.key,
         constraint = loan.mint == mint.key(),
-        constraint = loan.state == LoanState::Listed || loan.state == LoanState::Defaulted,
+        constraint = loan.state == LoanState::Listed || loan.state == LoanState::Defaulted || loan.state == LoanState::Repaid,
         close = borrower
     )]
     pub loan: Box<Account<'info, Loan>>,
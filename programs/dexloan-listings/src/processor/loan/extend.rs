@@ -0,0 +1,236 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Mint};
+use crate::state::{Loan, LoanState, LoanExtension, ProtocolConfig};
+use crate::error::*;
+use crate::utils::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LoanExtensionArgs {
+    duration: i64,
+    basis_points: Option<u32>,
+    capitalise: bool,
+}
+
+#[derive(Accounts)]
+pub struct ProposeLoanExtension<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+    /// CHECK: contrained on loan_account
+    #[account(mut)]
+    pub lender: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        bump,
+        has_one = borrower,
+        constraint = loan.note_mint.is_some() || loan.lender == lender.key(),
+        has_one = mint,
+        constraint = loan.state == LoanState::Active,
+        constraint = loan.notice_period.is_none(),
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        init_if_needed,
+        payer = borrower,
+        seeds = [
+            LoanExtension::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        space = LoanExtension::space(),
+        bump,
+    )]
+    pub loan_extension: Box<Account<'info, LoanExtension>>,
+    pub mint: Box<Account<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_propose_loan_extension<'info>(
+    ctx: Context<'_, '_, '_, 'info, ProposeLoanExtension<'info>>,
+    args: LoanExtensionArgs
+) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+    let loan_extension = &mut ctx.accounts.loan_extension;
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    if args.duration <= loan.duration {
+        return err!(DexloanError::InvalidDuration);
+    }
+
+    assert_lender_position(loan, &ctx.accounts.lender.to_account_info(), remaining_accounts)?;
+
+    // Refund any interest escrowed by an earlier proposal
+    transfer_from_escrow(
+        &mut loan_extension.to_account_info(),
+        &mut ctx.accounts.borrower.to_account_info(),
+        loan_extension.escrow_balance,
+    )?;
+    loan_extension.escrow_balance = 0;
+
+    // Accrued interest is escrowed until the lender accepts, unless it is to be capitalised
+    if !args.capitalise {
+        // Interest is escrowed in lamports
+        if loan.currency_mint.is_some() {
+            return err!(DexloanError::InvalidMint);
+        }
+
+        accrue_loan_interest(loan, unix_timestamp)?;
+
+        msg!("Escrowing {} of accrued interest", loan.outstanding_interest);
+
+        transfer_currency(
+            &None,
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.borrower.to_account_info(),
+            &ctx.accounts.borrower.to_account_info(),
+            &loan_extension.to_account_info(),
+            loan.outstanding_interest,
+        )?;
+        loan_extension.escrow_balance = loan.outstanding_interest;
+    }
+
+    loan_extension.duration = args.duration;
+    loan_extension.basis_points = args.basis_points;
+    loan_extension.capitalise = args.capitalise;
+    loan_extension.lender = loan.lender;
+    loan_extension.start_date = loan.start_date;
+    loan_extension.bump = *ctx.bumps.get("loan_extension").unwrap();
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptLoanExtension<'info> {
+    /// CHECK: contrained on loan_account
+    #[account(mut)]
+    pub borrower: AccountInfo<'info>,
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        bump,
+        has_one = borrower,
+        constraint = loan.note_mint.is_some() || loan.lender == lender.key(),
+        has_one = mint,
+        constraint = loan.state == LoanState::Active,
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        mut,
+        seeds = [
+            LoanExtension::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        bump = loan_extension.bump,
+        close = borrower,
+        has_one = lender,
+        constraint = loan_extension.start_date == loan.start_date,
+    )]
+    pub loan_extension: Box<Account<'info, LoanExtension>>,
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_accept_loan_extension<'info>(ctx: Context<'_, '_, '_, 'info, AcceptLoanExtension<'info>>) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+    let loan_extension = &mut ctx.accounts.loan_extension;
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    assert_lender_position(loan, &ctx.accounts.lender.to_account_info(), remaining_accounts)?;
+
+    // Interest up to now is accrued on the current terms
+    accrue_loan_interest(loan, unix_timestamp)?;
+
+    // Escrowed interest is settled now, and any surplus is refunded when the extension is closed
+    let interest_payment = loan_extension.escrow_balance.min(loan.outstanding_interest);
+
+    if interest_payment > 0 {
+        msg!("Settling {} of accrued interest", interest_payment);
+
+        loan.outstanding_interest -= interest_payment;
+        loan.interest_paid += interest_payment;
+
+        let protocol_fee = calculate_fee_from_basis_points(
+            interest_payment as u128,
+            ctx.accounts.protocol_config.loan_fee_basis_points as u128,
+        )?;
+
+        transfer_from_escrow(
+            &mut loan_extension.to_account_info(),
+            &mut ctx.accounts.lender.to_account_info(),
+            interest_payment - protocol_fee,
+        )?;
+        transfer_from_escrow(
+            &mut loan_extension.to_account_info(),
+            &mut ctx.accounts.treasury.to_account_info(),
+            protocol_fee,
+        )?;
+    }
+
+    if loan_extension.capitalise {
+        msg!("Capitalising {} lamports of accrued interest", loan.outstanding_interest);
+
+        loan.amount = loan.amount
+            .checked_add(loan.outstanding_interest)
+            .ok_or(DexloanError::NumericalOverflow)?;
+        loan.interest_paid += loan.outstanding_interest;
+        loan.outstanding_interest = 0;
+    }
+
+    loan.duration = loan_extension.duration;
+    // Late fees charged so far remain in the outstanding interest
+    loan.late_fees_charged = 0;
+
+    if let Some(basis_points) = loan_extension.basis_points {
+        loan.basis_points = basis_points;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelLoanExtension<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            LoanExtension::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        bump = loan_extension.bump,
+        close = borrower
+    )]
+    pub loan_extension: Box<Account<'info, LoanExtension>>,
+    pub mint: Box<Account<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_cancel_loan_extension(_ctx: Context<CancelLoanExtension>) -> Result<()> {
+    Ok(())
+}
\ No newline at end of file
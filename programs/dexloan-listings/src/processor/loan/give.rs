@@ -1,6 +1,9 @@
 use anchor_lang::{prelude::*};
 use anchor_spl::token::{Mint, Token};
 use crate::state::{Loan, LoanState, TokenManager};
+use crate::error::*;
+use crate::utils::*;
+use crate::processor::loan::LoanArgs;
 
 #[derive(Accounts)]
 pub struct GiveLoan<'info> {
@@ -20,6 +23,7 @@ pub struct GiveLoan<'info> {
         has_one = mint,
         has_one = borrower,
         constraint = loan.borrower != lender.key(),
+        constraint = loan.counterparty.is_none() || loan.counterparty == Some(lender.key()),
         constraint = loan.state == LoanState::Listed,
     )]
     pub loan: Box<Account<'info, Loan>>,
@@ -40,25 +44,50 @@ pub struct GiveLoan<'info> {
 }
 
 
-pub fn handle_give_loan(ctx: Context<GiveLoan>) -> Result<()> {
+pub fn handle_give_loan<'info>(ctx: Context<'_, '_, '_, 'info, GiveLoan<'info>>) -> Result<()> {
     let loan = &mut ctx.accounts.loan;
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+
+    if loan.is_listing_expired(ctx.accounts.clock.unix_timestamp) {
+        return err!(DexloanError::ListingExpired);
+    }
 
     loan.state = LoanState::Active;
     loan.lender = ctx.accounts.lender.key();
     loan.start_date = ctx.accounts.clock.unix_timestamp;
 
+    let borrower_currency_account = next_currency_account(
+        &loan.currency_mint,
+        &ctx.accounts.borrower.to_account_info(),
+        remaining_accounts,
+    )?;
+    let lender_currency_account = next_currency_account(
+        &loan.currency_mint,
+        &ctx.accounts.lender.to_account_info(),
+        remaining_accounts,
+    )?;
+
     // Transfer amount
-    anchor_lang::solana_program::program::invoke(
-        &anchor_lang::solana_program::system_instruction::transfer(
-            &loan.lender,
-            &loan.borrower,
-            loan.amount,
-        ),
-        &[
-            ctx.accounts.lender.to_account_info(),
-            ctx.accounts.borrower.to_account_info(),
-        ]
+    transfer_currency(
+        &loan.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.lender.to_account_info(),
+        &lender_currency_account,
+        &borrower_currency_account,
+        loan.amount,
     )?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+// Guards against the listing's terms being changed before the transaction lands
+pub fn handle_give_loan_with_terms<'info>(
+    ctx: Context<'_, '_, '_, 'info, GiveLoan<'info>>,
+    expected_terms: LoanArgs,
+) -> Result<()> {
+    if !expected_terms.matches(&ctx.accounts.loan) {
+        return err!(DexloanError::TermsMismatch);
+    }
+
+    handle_give_loan(ctx)
+}
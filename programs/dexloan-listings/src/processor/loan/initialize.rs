@@ -1,11 +1,91 @@
 use anchor_lang::{prelude::*};
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use crate::state::{Loan, LoanState, Hire, HireState, TokenManager};
+use crate::state::{Loan, LoanState, LoanDefaultMode, InterestMode, Hire, HireState, TokenManager};
 use crate::utils::*;
 use crate::error::*;
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LoanArgs {
+    pub amount: u64,
+    pub basis_points: u32,
+    pub duration: i64,
+    pub interest_mode: InterestMode,
+    pub minimum_fee: u64,
+    pub currency_mint: Option<Pubkey>,
+    pub liquidation_threshold: Option<u32>,
+    pub price_feed: Option<Pubkey>,
+    pub grace_period: i64,
+    pub late_fee_basis_points: u32,
+    pub default_mode: LoanDefaultMode,
+    pub notice_period: Option<i64>,
+    pub listing_expiry: Option<i64>,
+    pub counterparty: Option<Pubkey>,
+}
+
+impl LoanArgs {
+    pub fn validate(&self) -> Result<()> {
+        // A threshold needs a price feed to be checked against and can not exceed the full price
+        if self.liquidation_threshold.is_some() != self.price_feed.is_some() {
+            return err!(DexloanError::InvalidLiquidationThreshold);
+        }
+
+        if let Some(liquidation_threshold) = self.liquidation_threshold {
+            if liquidation_threshold == 0 || liquidation_threshold > 10_000 {
+                return err!(DexloanError::InvalidLiquidationThreshold);
+            }
+        }
+
+        if self.basis_points == 0 {
+            return err!(DexloanError::InvalidBasisPoints);
+        }
+
+        if self.grace_period < 0 || (self.notice_period.is_none() && self.duration <= 0) {
+            return err!(DexloanError::InvalidDuration);
+        }
+
+        // Callable loans have no fixed duration and accrue interest per second
+        if let Some(notice_period) = self.notice_period {
+            if notice_period <= 0 || self.duration != 0 || self.interest_mode == InterestMode::FullTerm {
+                return err!(DexloanError::InvalidDuration);
+            }
+        }
+
+        if let LoanDefaultMode::Auction { start_price, floor_price, duration } = self.default_mode {
+            // Auction proceeds are settled in lamports
+            if self.currency_mint.is_some() {
+                return err!(DexloanError::InvalidMint);
+            }
+
+            if floor_price > start_price {
+                return err!(DexloanError::InvalidAmount);
+            }
+
+            if duration <= 0 {
+                return err!(DexloanError::InvalidDuration);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the loan's economic terms are those given
+    pub fn matches(&self, loan: &Loan) -> bool {
+        loan.amount == self.amount &&
+        loan.basis_points == self.basis_points &&
+        loan.duration == self.duration &&
+        loan.interest_mode == self.interest_mode &&
+        loan.minimum_fee == self.minimum_fee &&
+        loan.currency_mint == self.currency_mint &&
+        loan.liquidation_threshold == self.liquidation_threshold &&
+        loan.price_feed == self.price_feed &&
+        loan.grace_period == self.grace_period &&
+        loan.late_fee_basis_points == self.late_fee_basis_points &&
+        loan.default_mode == self.default_mode &&
+        loan.notice_period == self.notice_period
+    }
+}
+
 #[derive(Accounts)]
-#[instruction(amount: u64, basis_points: u32, duration: u64)]
 pub struct InitLoan<'info> {
     #[account(mut)]
     pub borrower: Signer<'info>,
@@ -55,9 +135,7 @@ pub struct InitLoan<'info> {
 
 pub fn handle_init_loan(
   ctx: Context<InitLoan>,
-  amount: u64,
-  basis_points: u32,
-  duration: i64
+  args: LoanArgs,
 ) -> Result<()> {
     let loan = &mut ctx.accounts.loan;
     let token_manager = &mut ctx.accounts.token_manager;
@@ -65,22 +143,38 @@ pub fn handle_init_loan(
 
     require_eq!(token_manager.accounts.hire, false, DexloanError::InvalidState);
     require_eq!(token_manager.accounts.call_option, false, DexloanError::InvalidState);
+    require_eq!(token_manager.bundle, false, DexloanError::InvalidState);
+
+    args.validate()?;
 
     // Init
     loan.mint = ctx.accounts.mint.key();
     loan.borrower = ctx.accounts.borrower.key();
     loan.bump = *ctx.bumps.get("loan").unwrap();
     //
-    loan.amount = amount;
-    loan.basis_points = basis_points;
-    loan.duration = duration;
+    loan.amount = args.amount;
+    loan.basis_points = args.basis_points;
+    loan.duration = args.duration;
+    loan.interest_mode = args.interest_mode;
+    loan.minimum_fee = args.minimum_fee;
+    loan.currency_mint = args.currency_mint;
+    loan.liquidation_threshold = args.liquidation_threshold;
+    loan.price_feed = args.price_feed;
+    loan.grace_period = args.grace_period;
+    loan.late_fee_basis_points = args.late_fee_basis_points;
+    loan.default_mode = args.default_mode;
+    loan.notice_period = args.notice_period;
+    loan.listing_expiry = args.listing_expiry;
+    loan.counterparty = args.counterparty;
     loan.state = LoanState::Listed;
     //
     token_manager.accounts.loan = true;
     token_manager.bump = *ctx.bumps.get("token_manager").unwrap();
 
     if deposit_token_account.delegate.is_some() {
-        if !deposit_token_account.is_frozen() && deposit_token_account.delegate.unwrap() != token_manager.key()  {
+        // An unfrozen token is secured again whoever the delegate is, as a
+        // crank may have thawed it while leaving the delegation in place
+        if !deposit_token_account.is_frozen() {
             anchor_spl::token::revoke(
                 CpiContext::new(
                     ctx.accounts.token_program.to_account_info(),
@@ -120,7 +214,6 @@ pub fn handle_init_loan(
 
 
 #[derive(Accounts)]
-#[instruction(amount: u64, basis_points: u32, duration: u64)]
 pub struct InitLoanWithHire<'info> {
     #[account(mut)]
     pub borrower: Signer<'info>,
@@ -184,24 +277,36 @@ pub struct InitLoanWithHire<'info> {
 
 pub fn handle_init_loan_with_hire(
     ctx: Context<InitLoanWithHire>,
-    amount: u64,
-    basis_points: u32,
-    duration: i64
+    args: LoanArgs,
   ) -> Result<()> {
       let loan = &mut ctx.accounts.loan;
       let token_manager = &mut ctx.accounts.token_manager;
 
       require_eq!(token_manager.accounts.hire, true, DexloanError::InvalidState);
       require_eq!(token_manager.accounts.call_option, false, DexloanError::InvalidState);
+      require_eq!(token_manager.bundle, false, DexloanError::InvalidState);
+
+      args.validate()?;
   
       // Init
       loan.mint = ctx.accounts.mint.key();
       loan.borrower = ctx.accounts.borrower.key();
       loan.bump = *ctx.bumps.get("loan").unwrap();
       //
-      loan.amount = amount;
-      loan.basis_points = basis_points;
-      loan.duration = duration;
+      loan.amount = args.amount;
+      loan.basis_points = args.basis_points;
+      loan.duration = args.duration;
+      loan.interest_mode = args.interest_mode;
+      loan.minimum_fee = args.minimum_fee;
+      loan.currency_mint = args.currency_mint;
+      loan.liquidation_threshold = args.liquidation_threshold;
+      loan.price_feed = args.price_feed;
+      loan.grace_period = args.grace_period;
+      loan.late_fee_basis_points = args.late_fee_basis_points;
+      loan.default_mode = args.default_mode;
+      loan.notice_period = args.notice_period;
+      loan.listing_expiry = args.listing_expiry;
+      loan.counterparty = args.counterparty;
       loan.state = LoanState::Listed;
       //
       token_manager.accounts.loan = true;
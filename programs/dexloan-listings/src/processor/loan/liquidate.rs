@@ -0,0 +1,106 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Token, TokenAccount, Mint};
+use crate::state::{Loan, LoanState, ProtocolConfig, TokenManager};
+use crate::error::{DexloanError};
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct LiquidateLoan<'info> {
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    /// CHECK: contrained on loan_account
+    #[account(mut)]
+    pub borrower: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = lender
+    )]
+    pub lender_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = borrower,
+    )]
+    pub deposit_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        bump,
+        has_one = borrower,
+        constraint = loan.note_mint.is_some() || loan.lender == lender.key(),
+        has_one = mint,
+        constraint = loan.state == LoanState::Active || loan.state == LoanState::Recalled,
+        constraint = loan.price_feed.is_some() && loan.price_feed.unwrap() == price_feed.key(),
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        mut,
+        seeds = [
+            TokenManager::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref()
+        ],
+        bump,
+        constraint = !token_manager.accounts.hire,
+    )]   
+    pub token_manager: Box<Account<'info, TokenManager>>,
+    /// CHECK: contrained on loan_account and deserialized
+    pub price_feed: UncheckedAccount<'info>,
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: validated in cpi
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub metadata_program: UncheckedAccount<'info>, 
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_liquidate_loan<'info>(ctx: Context<'_, '_, '_, 'info, LiquidateLoan<'info>>) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let token_manager = &mut ctx.accounts.token_manager;
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    assert_lender_position(loan, &ctx.accounts.lender.to_account_info(), remaining_accounts)?;
+
+    let liquidation_threshold = loan.liquidation_threshold.ok_or(DexloanError::InvalidLiquidationThreshold)?;
+    let floor_price = read_price_feed(
+        &ctx.accounts.price_feed.to_account_info(),
+        &ctx.accounts.protocol_config.oracle_program,
+        unix_timestamp,
+    )?;
+    let liquidation_amount = calculate_fee_from_basis_points(floor_price as u128, liquidation_threshold as u128)?;
+
+    msg!("Floor price {}, liquidation amount {}", floor_price, liquidation_amount);
+
+    if liquidation_amount >= loan.amount {
+        return err!(DexloanError::NotLiquidatable);
+    }
+
+    loan.state = LoanState::Defaulted;
+    token_manager.accounts.loan = false;
+
+    thaw_and_transfer_from_token_account(
+        token_manager,
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.borrower.to_account_info(),
+        ctx.accounts.deposit_token_account.to_account_info(),
+        ctx.accounts.lender_token_account.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.edition.to_account_info()
+    )?;
+
+    Ok(())
+}
\ No newline at end of file
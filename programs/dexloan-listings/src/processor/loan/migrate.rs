@@ -0,0 +1,26 @@
+use anchor_lang::{prelude::*, Discriminator};
+use crate::state::{Loan};
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct MigrateLoan<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: can't be deserialized until migrated, checked in handler
+    #[account(mut, owner = crate::ID)]
+    pub loan: UncheckedAccount<'info>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+}
+
+// Anyone may pay to extend a loan created before the current layout
+pub fn handle_migrate_loan(ctx: Context<MigrateLoan>) -> Result<()> {
+    migrate_account(
+        &ctx.accounts.loan.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        Loan::discriminator(),
+        Loan::legacy_space(),
+        Loan::space(),
+    )
+}
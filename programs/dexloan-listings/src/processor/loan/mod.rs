@@ -1,11 +1,29 @@
+pub mod auction;
 pub mod close;
+pub mod extend;
 pub mod initialize;
 pub mod give;
+pub mod liquidate;
+pub mod migrate;
+pub mod note;
+pub mod recall;
+pub mod refinance;
 pub mod repay;
 pub mod repossess;
+pub mod transfer;
+pub mod update;
 
+pub use auction::*;
 pub use close::*;
+pub use extend::*;
 pub use initialize::*;
 pub use give::*;
+pub use liquidate::*;
+pub use migrate::*;
+pub use note::*;
+pub use recall::*;
+pub use refinance::*;
 pub use repay::*;
-pub use repossess::*;
\ No newline at end of file
+pub use repossess::*;
+pub use transfer::*;
+pub use update::*;
\ No newline at end of file
@@ -0,0 +1,100 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount},
+};
+use crate::state::{Loan, LoanState};
+
+#[derive(Accounts)]
+pub struct MintLoanNote<'info> {
+    /// CHECK: contrained on loan_account
+    pub borrower: AccountInfo<'info>,
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        bump,
+        has_one = borrower,
+        has_one = lender,
+        has_one = mint,
+        constraint = loan.state == LoanState::Active || loan.state == LoanState::Recalled,
+        constraint = loan.note_mint.is_none(),
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        init,
+        payer = lender,
+        seeds = [
+            Loan::NOTE_PREFIX,
+            loan.key().as_ref(),
+            &loan.start_date.to_le_bytes(),
+        ],
+        bump,
+        mint::decimals = 0,
+        mint::authority = loan,
+    )]
+    pub note_mint: Box<Account<'info, Mint>>,
+    #[account(
+        init_if_needed,
+        payer = lender,
+        associated_token::mint = note_mint,
+        associated_token::authority = lender,
+    )]
+    pub note_token_account: Box<Account<'info, TokenAccount>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_mint_loan_note(ctx: Context<MintLoanNote>) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+
+    loan.note_mint = Some(ctx.accounts.note_mint.key());
+
+    let mint = ctx.accounts.mint.key();
+    let borrower = ctx.accounts.borrower.key();
+    let signer_bump = &[loan.bump];
+    let signer_seeds = &[&[
+        Loan::PREFIX,
+        mint.as_ref(),
+        borrower.as_ref(),
+        signer_bump
+    ][..]];
+
+    token::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::MintTo {
+                mint: ctx.accounts.note_mint.to_account_info(),
+                to: ctx.accounts.note_token_account.to_account_info(),
+                authority: loan.to_account_info(),
+            },
+            signer_seeds
+        ),
+        1
+    )?;
+
+    // Remove the mint authority so the note remains a 1/1
+    token::set_authority(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::SetAuthority {
+                account_or_mint: ctx.accounts.note_mint.to_account_info(),
+                current_authority: loan.to_account_info(),
+            },
+            signer_seeds
+        ),
+        spl_token::instruction::AuthorityType::MintTokens,
+        None
+    )?;
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,45 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Mint};
+use crate::state::{Loan, LoanState};
+use crate::error::*;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct RecallLoan<'info> {
+    /// CHECK: contrained on loan_account
+    pub borrower: AccountInfo<'info>,
+    pub lender: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        bump,
+        has_one = borrower,
+        constraint = loan.note_mint.is_some() || loan.lender == lender.key(),
+        has_one = mint,
+        constraint = loan.state == LoanState::Active,
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    pub mint: Box<Account<'info, Mint>>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_recall_loan<'info>(ctx: Context<'_, '_, '_, 'info, RecallLoan<'info>>) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+
+    assert_lender_position(loan, &ctx.accounts.lender.to_account_info(), remaining_accounts)?;
+
+    if loan.notice_period.is_none() {
+        return err!(DexloanError::InvalidState);
+    }
+
+    // The borrower must repay before the notice period ends
+    loan.state = LoanState::Recalled;
+    loan.recalled_at = ctx.accounts.clock.unix_timestamp;
+
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Mint, Token};
+use crate::state::{Loan, LoanState, ProtocolConfig};
+use crate::error::*;
+use crate::utils::*;
+use crate::processor::loan::LoanArgs;
+
+#[derive(Accounts)]
+pub struct RefinanceLoan<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+    /// CHECK: contrained on loan_account
+    #[account(mut)]
+    pub lender: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = new_lender.key() != lender.key(),
+    )]
+    pub new_lender: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        bump,
+        has_one = borrower,
+        constraint = loan.note_mint.is_some() || loan.lender == lender.key(),
+        has_one = mint,
+        constraint = loan.borrower != new_lender.key(),
+        constraint = loan.state == LoanState::Active || loan.state == LoanState::Recalled,
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_refinance_loan<'info>(ctx: Context<'_, '_, '_, 'info, RefinanceLoan<'info>>, args: LoanArgs) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let borrower = ctx.accounts.borrower.to_account_info();
+    let lender = ctx.accounts.lender.to_account_info();
+    let new_lender = ctx.accounts.new_lender.to_account_info();
+    let treasury = ctx.accounts.treasury.to_account_info();
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    if args.currency_mint != loan.currency_mint {
+        return err!(DexloanError::InvalidMint);
+    }
+
+    args.validate()?;
+
+    assert_lender_position(loan, &lender, remaining_accounts)?;
+
+    let borrower_currency_account = next_currency_account(&loan.currency_mint, &borrower, remaining_accounts)?;
+    let lender_currency_account = next_currency_account(&loan.currency_mint, &lender, remaining_accounts)?;
+    let new_lender_currency_account = next_currency_account(&loan.currency_mint, &new_lender, remaining_accounts)?;
+    let treasury_currency_account = next_currency_account(&loan.currency_mint, &treasury, remaining_accounts)?;
+
+    let amount_due = calculate_loan_amount_due(loan, unix_timestamp)?;
+    let new_lender_payment = amount_due.min(args.amount);
+    // The protocol fee is only taken from interest
+    let protocol_fee = calculate_fee_from_basis_points(
+        (amount_due - loan.amount) as u128,
+        ctx.accounts.protocol_config.loan_fee_basis_points as u128,
+    )?;
+    // The new lender's payment covers the fee first, then the borrower's
+    let new_lender_fee = protocol_fee.min(new_lender_payment);
+    let borrower_fee = protocol_fee - new_lender_fee;
+
+    msg!("Repaying {} to current lender", amount_due);
+
+    // New lender settles the current lender
+    transfer_currency(
+        &loan.currency_mint,
+        &token_program,
+        &new_lender,
+        &new_lender_currency_account,
+        &lender_currency_account,
+        new_lender_payment - new_lender_fee,
+    )?;
+    transfer_currency(
+        &loan.currency_mint,
+        &token_program,
+        &new_lender,
+        &new_lender_currency_account,
+        &treasury_currency_account,
+        new_lender_fee,
+    )?;
+
+    // Borrower covers any shortfall when the new amount is lower than the amount due
+    if amount_due > new_lender_payment {
+        transfer_currency(
+            &loan.currency_mint,
+            &token_program,
+            &borrower,
+            &borrower_currency_account,
+            &lender_currency_account,
+            amount_due - new_lender_payment - borrower_fee,
+        )?;
+        transfer_currency(
+            &loan.currency_mint,
+            &token_program,
+            &borrower,
+            &borrower_currency_account,
+            &treasury_currency_account,
+            borrower_fee,
+        )?;
+    }
+
+    // Any surplus is lent to the borrower
+    if args.amount > new_lender_payment {
+        transfer_currency(
+            &loan.currency_mint,
+            &token_program,
+            &new_lender,
+            &new_lender_currency_account,
+            &borrower_currency_account,
+            args.amount - new_lender_payment,
+        )?;
+    }
+
+    // The old note no longer carries the lender position
+    loan.note_mint = None;
+    loan.lender = ctx.accounts.new_lender.key();
+    loan.amount = args.amount;
+    loan.basis_points = args.basis_points;
+    loan.duration = args.duration;
+    loan.interest_mode = args.interest_mode;
+    loan.minimum_fee = args.minimum_fee;
+    loan.liquidation_threshold = args.liquidation_threshold;
+    loan.price_feed = args.price_feed;
+    loan.grace_period = args.grace_period;
+    loan.late_fee_basis_points = args.late_fee_basis_points;
+    loan.default_mode = args.default_mode;
+    loan.notice_period = args.notice_period;
+    loan.late_fees_charged = 0;
+    loan.state = LoanState::Active;
+    loan.recalled_at = 0;
+    loan.start_date = unix_timestamp;
+    loan.outstanding_interest = 0;
+    loan.interest_paid = 0;
+    loan.accrued_at = 0;
+
+    Ok(())
+}
\ No newline at end of file
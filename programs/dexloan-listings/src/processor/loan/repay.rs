@@ -1,11 +1,7 @@
-use anchor_lang::{
-  prelude::*,
-  solana_program::{
-      program::{invoke},
-  }
-};
+use anchor_lang::{prelude::*};
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use crate::state::{Loan, LoanState, TokenManager};
+use crate::state::{Loan, LoanState, ProtocolConfig, TokenManager};
+use crate::error::*;
 use crate::utils::*;
 
 #[derive(Accounts)]
@@ -29,9 +25,9 @@ pub struct RepayLoan<'info> {
         ],
         bump,
         has_one = borrower,
-        has_one = lender,
+        constraint = loan.note_mint.is_some() || loan.lender == lender.key(),
         has_one = mint,
-        constraint = loan.state == LoanState::Active,
+        constraint = loan.state == LoanState::Active || loan.state == LoanState::Recalled,
         close = borrower
     )]
     pub loan: Box<Account<'info, Loan>>,
@@ -45,6 +41,15 @@ pub struct RepayLoan<'info> {
         bump,
     )]   
     pub token_manager: Box<Account<'info, TokenManager>>,
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
     pub mint: Box<Account<'info, Mint>>,
     /// CHECK: validated in cpi
     pub edition: UncheckedAccount<'info>,
@@ -55,29 +60,57 @@ pub struct RepayLoan<'info> {
     pub clock: Sysvar<'info, Clock>,
 }
 
-pub fn handle_repay_loan(ctx: Context<RepayLoan>) -> Result<()> {
+pub fn handle_repay_loan<'info>(ctx: Context<'_, '_, '_, 'info, RepayLoan<'info>>) -> Result<()> {
     let loan = &mut ctx.accounts.loan;
     let token_manager = &mut ctx.accounts.token_manager;
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+
+    assert_lender_position(loan, &ctx.accounts.lender.to_account_info(), remaining_accounts)?;
 
     token_manager.accounts.loan = false;
 
-    let amount_due = calculate_loan_repayment(
-        loan.amount,
-        loan.basis_points,
-        loan.duration
+    let amount_due = calculate_loan_amount_due(
+        loan,
+        ctx.accounts.clock.unix_timestamp
+    )?;
+    // The protocol fee is only taken from interest
+    let protocol_fee = calculate_fee_from_basis_points(
+        (amount_due - loan.amount) as u128,
+        ctx.accounts.protocol_config.loan_fee_basis_points as u128,
+    )?;
+
+    let borrower_currency_account = next_currency_account(
+        &loan.currency_mint,
+        &ctx.accounts.borrower.to_account_info(),
+        remaining_accounts,
+    )?;
+    let lender_currency_account = next_currency_account(
+        &loan.currency_mint,
+        &ctx.accounts.lender.to_account_info(),
+        remaining_accounts,
+    )?;
+    let treasury_currency_account = next_currency_account(
+        &loan.currency_mint,
+        &ctx.accounts.treasury.to_account_info(),
+        remaining_accounts,
     )?;
 
     // Transfer payment
-    invoke(
-        &anchor_lang::solana_program::system_instruction::transfer(
-            &loan.borrower,
-            &loan.lender,
-            amount_due,
-        ),
-        &[
-            ctx.accounts.borrower.to_account_info(),
-            ctx.accounts.lender.to_account_info(),
-        ]
+    transfer_currency(
+        &loan.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.borrower.to_account_info(),
+        &borrower_currency_account,
+        &lender_currency_account,
+        amount_due - protocol_fee,
+    )?;
+    transfer_currency(
+        &loan.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.borrower.to_account_info(),
+        &borrower_currency_account,
+        &treasury_currency_account,
+        protocol_fee,
     )?;
 
     if token_manager.accounts.hire == false {
@@ -91,5 +124,148 @@ pub fn handle_repay_loan(ctx: Context<RepayLoan>) -> Result<()> {
         )?;
     }
 
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct RepayLoanPartial<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+    #[account(
+        mut,
+        constraint = deposit_token_account.owner == borrower.key(),
+    )]
+    pub deposit_token_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: contrained on loan_account
+    #[account(mut)]
+    pub lender: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        bump,
+        has_one = borrower,
+        constraint = loan.note_mint.is_some() || loan.lender == lender.key(),
+        has_one = mint,
+        constraint = loan.state == LoanState::Active || loan.state == LoanState::Recalled,
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        mut,
+        seeds = [
+            TokenManager::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref()
+        ],
+        bump,
+    )]   
+    pub token_manager: Box<Account<'info, TokenManager>>,
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: validated in cpi
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub metadata_program: UncheckedAccount<'info>, 
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_repay_loan_partial<'info>(ctx: Context<'_, '_, '_, 'info, RepayLoanPartial<'info>>, amount: u64) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let token_manager = &mut ctx.accounts.token_manager;
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    if amount == 0 {
+        return err!(DexloanError::InvalidAmount);
+    }
+
+    assert_lender_position(loan, &ctx.accounts.lender.to_account_info(), remaining_accounts)?;
+
+    accrue_loan_interest(loan, unix_timestamp)?;
+
+    let amount_due = calculate_loan_amount_due(loan, unix_timestamp)?;
+    let payment = amount.min(amount_due);
+    // Outstanding interest is repaid before principal
+    let interest_payment = payment.min(loan.outstanding_interest);
+    let principal_payment = (payment - interest_payment).min(loan.amount);
+
+    loan.outstanding_interest -= interest_payment;
+    loan.interest_paid += payment - principal_payment;
+    loan.amount -= principal_payment;
+
+    msg!("Repaying {} of {} due", payment, amount_due);
+
+    // The protocol fee is only taken from interest
+    let protocol_fee = calculate_fee_from_basis_points(
+        (payment - principal_payment) as u128,
+        ctx.accounts.protocol_config.loan_fee_basis_points as u128,
+    )?;
+
+    let borrower_currency_account = next_currency_account(
+        &loan.currency_mint,
+        &ctx.accounts.borrower.to_account_info(),
+        remaining_accounts,
+    )?;
+    let lender_currency_account = next_currency_account(
+        &loan.currency_mint,
+        &ctx.accounts.lender.to_account_info(),
+        remaining_accounts,
+    )?;
+    let treasury_currency_account = next_currency_account(
+        &loan.currency_mint,
+        &ctx.accounts.treasury.to_account_info(),
+        remaining_accounts,
+    )?;
+
+    // Transfer payment
+    transfer_currency(
+        &loan.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.borrower.to_account_info(),
+        &borrower_currency_account,
+        &lender_currency_account,
+        payment - protocol_fee,
+    )?;
+    transfer_currency(
+        &loan.currency_mint,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.borrower.to_account_info(),
+        &borrower_currency_account,
+        &treasury_currency_account,
+        protocol_fee,
+    )?;
+
+    if payment < amount_due {
+        return Ok(());
+    }
+
+    loan.state = LoanState::Repaid;
+    token_manager.accounts.loan = false;
+
+    if !token_manager.accounts.hire {
+        thaw_and_revoke_token_account(
+            token_manager,
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.deposit_token_account.to_account_info(),
+            ctx.accounts.borrower.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.edition.to_account_info()
+        )?;
+    }
+
     Ok(())
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
 use anchor_lang::{prelude::*};
 use anchor_spl::token::{Token, TokenAccount, Mint};
-use crate::state::{Loan, LoanState, Hire, TokenManager};
+use crate::state::{Loan, LoanState, LoanDefaultMode, Hire, ProtocolConfig, TokenManager};
 use crate::error::{DexloanError};
 use crate::utils::*;
 
@@ -32,9 +32,9 @@ pub struct Repossess<'info> {
         ],
         bump,
         has_one = borrower,
-        has_one = lender,
+        constraint = loan.note_mint.is_some() || loan.lender == lender.key(),
         has_one = mint,
-        constraint = loan.state == LoanState::Active,
+        constraint = loan.state == LoanState::Active || loan.state == LoanState::Recalled || loan.state == LoanState::Auctioning,
     )]
     pub loan: Box<Account<'info, Loan>>,
     #[account(
@@ -60,16 +60,39 @@ pub struct Repossess<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn handle_repossess(ctx: Context<Repossess>) -> Result<()> {
+pub fn handle_repossess<'info>(ctx: Context<'_, '_, '_, 'info, Repossess<'info>>) -> Result<()> {
   let loan = &mut ctx.accounts.loan;
   let token_manager = &mut ctx.accounts.token_manager;
+  let remaining_accounts = &mut ctx.remaining_accounts.iter();
+
+  assert_lender_position(loan, &ctx.accounts.lender.to_account_info(), remaining_accounts)?;
   
   let unix_timestamp = ctx.accounts.clock.unix_timestamp;
-  let start_date = loan.start_date;
-  let duration = unix_timestamp - start_date;
 
-  if loan.duration > duration  {
-      return Err(DexloanError::NotOverdue.into())
+  if loan.state == LoanState::Auctioning {
+      // The lender may only claim the NFT once the auction has ended unsold
+      if unix_timestamp < calculate_auction_end(loan)? {
+          return Err(DexloanError::AuctionNotEnded.into())
+      }
+  } else {
+      let maturity = loan.maturity().ok_or(DexloanError::NotOverdue)?;
+
+      // Borrowers may still repay with a late fee during the grace period
+      let grace_period_end = maturity
+          .checked_add(loan.grace_period)
+          .ok_or(DexloanError::NumericalOverflow)?;
+
+      if grace_period_end > unix_timestamp  {
+          return Err(DexloanError::NotOverdue.into())
+      }
+
+      // The NFT remains frozen while it is auctioned
+      if let LoanDefaultMode::Auction { .. } = loan.default_mode {
+          loan.state = LoanState::Auctioning;
+          loan.auction_start = unix_timestamp;
+
+          return Ok(());
+      }
   }
   
   loan.state = LoanState::Defaulted;
@@ -110,9 +133,9 @@ pub struct RepossessWithHire<'info> {
         ],
         bump,
         has_one = borrower,
-        has_one = lender,
+        constraint = loan.note_mint.is_some() || loan.lender == lender.key(),
         has_one = mint,
-        constraint = loan.state == LoanState::Active,
+        constraint = loan.state == LoanState::Active || loan.state == LoanState::Recalled,
     )]
     pub loan: Box<Account<'info, Loan>>,
     #[account(
@@ -152,6 +175,15 @@ pub struct RepossessWithHire<'info> {
         bump,
     )]   
     pub token_manager: Box<Account<'info, TokenManager>>,
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
     /// CHECK: contrained on loan_account
     pub mint: Account<'info, Mint>,
     /// CHECK: validated in cpi
@@ -171,13 +203,21 @@ pub fn handle_repossess_with_hire<'info>(ctx: Context<'_, '_, '_, 'info, Reposse
     let remaining_accounts = &mut ctx.remaining_accounts.iter();
     let unix_timestamp = ctx.accounts.clock.unix_timestamp;
 
-    let start_date = loan.start_date;
-    let duration = unix_timestamp - start_date;
+    assert_lender_position(loan, &ctx.accounts.lender.to_account_info(), remaining_accounts)?;
+
+    let maturity = loan.maturity().ok_or(DexloanError::NotOverdue)?;
+
+    // Borrowers may still repay with a late fee during the grace period
+    let grace_period_end = maturity
+        .checked_add(loan.grace_period)
+        .ok_or(DexloanError::NumericalOverflow)?;
 
-    if loan.duration > duration  {
+    if grace_period_end > unix_timestamp  {
         return Err(DexloanError::NotOverdue.into())
     }
 
+    // Rented NFTs are held by the renter and so are always repossessed outright,
+    // regardless of the loan's default mode
     loan.state = LoanState::Defaulted;
     token_manager.accounts.loan = false;
     token_manager.accounts.hire = false;
@@ -188,6 +228,8 @@ pub fn handle_repossess_with_hire<'info>(ctx: Context<'_, '_, '_, 'info, Reposse
             remaining_accounts,
             &ctx.accounts.hire_escrow.to_account_info(),
             &ctx.accounts.borrower.to_account_info(),
+            &ctx.accounts.protocol_config,
+            &ctx.accounts.treasury.to_account_info(),
             unix_timestamp,
         )?;
     }
@@ -0,0 +1,77 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Mint, Token};
+use crate::state::{Loan, LoanState};
+use crate::error::{DexloanError};
+use crate::utils::*;
+
+#[derive(Accounts)]
+#[instruction(amount: u64)]
+pub struct TransferLenderPosition<'info> {
+    /// CHECK: contrained on loan_account
+    pub borrower: AccountInfo<'info>,
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    /// CHECK: must sign when the position is sold
+    #[account(mut)]
+    pub new_lender: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        bump,
+        has_one = borrower,
+        has_one = lender,
+        has_one = mint,
+        constraint = loan.state == LoanState::Active || loan.state == LoanState::Recalled,
+        constraint = loan.note_mint.is_none(),
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handle_transfer_lender_position<'info>(
+    ctx: Context<'_, '_, '_, 'info, TransferLenderPosition<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+
+    if ctx.accounts.new_lender.key() == loan.lender {
+        return err!(DexloanError::InvalidState);
+    }
+
+    // A gift requires no payment from the new lender
+    if amount > 0 {
+        require!(ctx.accounts.new_lender.is_signer, ErrorCode::AccountNotSigner);
+
+        let new_lender_currency_account = next_currency_account(
+            &loan.currency_mint,
+            &ctx.accounts.new_lender.to_account_info(),
+            remaining_accounts,
+        )?;
+        let lender_currency_account = next_currency_account(
+            &loan.currency_mint,
+            &ctx.accounts.lender.to_account_info(),
+            remaining_accounts,
+        )?;
+
+        transfer_currency(
+            &loan.currency_mint,
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.new_lender.to_account_info(),
+            &new_lender_currency_account,
+            &lender_currency_account,
+            amount,
+        )?;
+    }
+
+    loan.lender = ctx.accounts.new_lender.key();
+
+    Ok(())
+}
\ No newline at end of file
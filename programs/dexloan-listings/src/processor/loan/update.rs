@@ -0,0 +1,50 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Mint};
+use crate::state::{Loan, LoanState};
+use crate::processor::loan::LoanArgs;
+
+#[derive(Accounts)]
+pub struct UpdateLoan<'info> {
+    pub borrower: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        bump,
+        has_one = borrower,
+        has_one = mint,
+        constraint = loan.state == LoanState::Listed,
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    pub mint: Box<Account<'info, Mint>>,
+}
+
+pub fn handle_update_loan(
+  ctx: Context<UpdateLoan>,
+  args: LoanArgs,
+) -> Result<()> {
+    let loan = &mut ctx.accounts.loan;
+
+    args.validate()?;
+
+    // The deposit remains frozen under the token manager
+    loan.amount = args.amount;
+    loan.basis_points = args.basis_points;
+    loan.duration = args.duration;
+    loan.interest_mode = args.interest_mode;
+    loan.minimum_fee = args.minimum_fee;
+    loan.currency_mint = args.currency_mint;
+    loan.liquidation_threshold = args.liquidation_threshold;
+    loan.price_feed = args.price_feed;
+    loan.grace_period = args.grace_period;
+    loan.late_fee_basis_points = args.late_fee_basis_points;
+    loan.default_mode = args.default_mode;
+    loan.notice_period = args.notice_period;
+    loan.listing_expiry = args.listing_expiry;
+    loan.counterparty = args.counterparty;
+
+    Ok(())
+}
\ No newline at end of file
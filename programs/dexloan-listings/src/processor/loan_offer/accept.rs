@@ -0,0 +1,325 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::state::{CollectionLoanOffer, Loan, LoanState, LoanOffer, InterestMode, TokenManager};
+use crate::error::*;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct AcceptLoanOffer<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+    /// CHECK: contrained on loan_offer
+    #[account(mut)]
+    pub lender: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            LoanOffer::PREFIX,
+            mint.key().as_ref(),
+            lender.key().as_ref(),
+        ],
+        bump = loan_offer.bump,
+        has_one = lender,
+        has_one = mint,
+        constraint = loan_offer.lender != borrower.key(),
+        close = lender
+    )]
+    pub loan_offer: Box<Account<'info, LoanOffer>>,
+    #[account(
+        mut,
+        constraint = deposit_token_account.owner == borrower.key(),
+        constraint = deposit_token_account.amount == 1,
+        associated_token::mint = mint,
+        associated_token::authority = borrower,
+    )]
+    pub deposit_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = borrower,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        space = Loan::space(),
+        bump,
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        init_if_needed,
+        payer = borrower,
+        seeds = [
+            TokenManager::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref()
+        ],
+        space = TokenManager::space(),
+        bump,
+    )]   
+    pub token_manager: Box<Account<'info, TokenManager>>,
+    #[account(constraint = mint.supply == 1)]
+    pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: validated in cpi
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub metadata_program: UncheckedAccount<'info>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_accept_loan_offer(
+    ctx: Context<AcceptLoanOffer>,
+    expected_amount: u64,
+    expected_basis_points: u32,
+    expected_duration: i64,
+) -> Result<()> {
+    let loan_offer = &ctx.accounts.loan_offer;
+    let loan = &mut ctx.accounts.loan;
+    let token_manager = &mut ctx.accounts.token_manager;
+    let deposit_token_account = &ctx.accounts.deposit_token_account;
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    if unix_timestamp > loan_offer.expiry {
+        return err!(DexloanError::OfferExpired);
+    }
+
+    // Guards against the offer being replaced before the transaction lands
+    if loan_offer.amount != expected_amount || loan_offer.basis_points != expected_basis_points || loan_offer.duration != expected_duration {
+        return err!(DexloanError::TermsMismatch);
+    }
+
+    require_eq!(token_manager.accounts.hire, false, DexloanError::InvalidState);
+    require_eq!(token_manager.accounts.call_option, false, DexloanError::InvalidState);
+    require_eq!(token_manager.bundle, false, DexloanError::InvalidState);
+
+    // Init
+    loan.mint = ctx.accounts.mint.key();
+    loan.borrower = ctx.accounts.borrower.key();
+    loan.bump = *ctx.bumps.get("loan").unwrap();
+    //
+    loan.amount = loan_offer.amount;
+    loan.basis_points = loan_offer.basis_points;
+    loan.duration = loan_offer.duration;
+    loan.interest_mode = InterestMode::FullTerm;
+    loan.lender = loan_offer.lender;
+    loan.start_date = unix_timestamp;
+    loan.state = LoanState::Active;
+    //
+    token_manager.accounts.loan = true;
+    token_manager.bump = *ctx.bumps.get("token_manager").unwrap();
+
+    if deposit_token_account.delegate.is_some() {
+        // An unfrozen token is secured again whoever the delegate is, as a
+        // crank may have thawed it while leaving the delegation in place
+        if !deposit_token_account.is_frozen() {
+            anchor_spl::token::revoke(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    anchor_spl::token::Revoke {
+                        source: deposit_token_account.to_account_info(),
+                        authority: ctx.accounts.borrower.to_account_info(),
+                    }
+                )
+            )?;
+
+            delegate_and_freeze_token_account(
+                token_manager,
+                ctx.accounts.token_program.to_account_info(),
+                deposit_token_account.to_account_info(),
+                ctx.accounts.borrower.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.edition.to_account_info(),
+                ctx.accounts.borrower.to_account_info(),
+            )?;
+        } else if deposit_token_account.delegate.unwrap() != token_manager.key() || deposit_token_account.delegated_amount != 1 {
+            return err!(DexloanError::InvalidDelegate);
+        }
+    } else {
+        delegate_and_freeze_token_account(
+            token_manager,
+            ctx.accounts.token_program.to_account_info(),
+            deposit_token_account.to_account_info(),
+            ctx.accounts.borrower.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.edition.to_account_info(),
+            ctx.accounts.borrower.to_account_info(),
+        )?;
+    }
+
+    msg!("Paying {} lamports to borrower from loan offer escrow", loan.amount);
+
+    transfer_from_escrow(
+        &mut ctx.accounts.loan_offer.to_account_info(),
+        &mut ctx.accounts.borrower.to_account_info(),
+        loan.amount,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptCollectionLoanOffer<'info> {
+    #[account(mut)]
+    pub borrower: Signer<'info>,
+    /// CHECK: contrained on collection_loan_offer
+    pub lender: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            CollectionLoanOffer::PREFIX,
+            collection_loan_offer.collection.as_ref(),
+            lender.key().as_ref(),
+        ],
+        bump = collection_loan_offer.bump,
+        has_one = lender,
+        constraint = collection_loan_offer.lender != borrower.key(),
+        constraint = collection_loan_offer.count > 0,
+    )]
+    pub collection_loan_offer: Box<Account<'info, CollectionLoanOffer>>,
+    #[account(
+        mut,
+        constraint = deposit_token_account.owner == borrower.key(),
+        constraint = deposit_token_account.amount == 1,
+        associated_token::mint = mint,
+        associated_token::authority = borrower,
+    )]
+    pub deposit_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = borrower,
+        seeds = [
+            Loan::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref(),
+        ],
+        space = Loan::space(),
+        bump,
+    )]
+    pub loan: Box<Account<'info, Loan>>,
+    #[account(
+        init_if_needed,
+        payer = borrower,
+        seeds = [
+            TokenManager::PREFIX,
+            mint.key().as_ref(),
+            borrower.key().as_ref()
+        ],
+        space = TokenManager::space(),
+        bump,
+    )]   
+    pub token_manager: Box<Account<'info, TokenManager>>,
+    #[account(constraint = mint.supply == 1)]
+    pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: validated in cpi
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: deserialized and checked
+    pub metadata: UncheckedAccount<'info>,
+    /// CHECK: validated in cpi
+    pub metadata_program: UncheckedAccount<'info>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_accept_collection_loan_offer(
+    ctx: Context<AcceptCollectionLoanOffer>,
+    expected_amount: u64,
+    expected_basis_points: u32,
+    expected_duration: i64,
+) -> Result<()> {
+    let collection_loan_offer = &mut ctx.accounts.collection_loan_offer;
+    let loan = &mut ctx.accounts.loan;
+    let token_manager = &mut ctx.accounts.token_manager;
+    let deposit_token_account = &ctx.accounts.deposit_token_account;
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    if unix_timestamp > collection_loan_offer.expiry {
+        return err!(DexloanError::OfferExpired);
+    }
+
+    // Guards against the offer being replaced before the transaction lands
+    if collection_loan_offer.amount != expected_amount || collection_loan_offer.basis_points != expected_basis_points || collection_loan_offer.duration != expected_duration {
+        return err!(DexloanError::TermsMismatch);
+    }
+
+    assert_metadata_collection(
+        &ctx.accounts.metadata.to_account_info(),
+        &ctx.accounts.mint.to_account_info(),
+        &collection_loan_offer.collection,
+    )?;
+
+    require_eq!(token_manager.accounts.hire, false, DexloanError::InvalidState);
+    require_eq!(token_manager.accounts.call_option, false, DexloanError::InvalidState);
+    require_eq!(token_manager.bundle, false, DexloanError::InvalidState);
+
+    collection_loan_offer.count -= 1;
+
+    // Init
+    loan.mint = ctx.accounts.mint.key();
+    loan.borrower = ctx.accounts.borrower.key();
+    loan.bump = *ctx.bumps.get("loan").unwrap();
+    //
+    loan.amount = collection_loan_offer.amount;
+    loan.basis_points = collection_loan_offer.basis_points;
+    loan.duration = collection_loan_offer.duration;
+    loan.interest_mode = InterestMode::FullTerm;
+    loan.lender = collection_loan_offer.lender;
+    loan.start_date = unix_timestamp;
+    loan.state = LoanState::Active;
+    //
+    token_manager.accounts.loan = true;
+    token_manager.bump = *ctx.bumps.get("token_manager").unwrap();
+
+    if deposit_token_account.delegate.is_some() {
+        // An unfrozen token is secured again whoever the delegate is, as a
+        // crank may have thawed it while leaving the delegation in place
+        if !deposit_token_account.is_frozen() {
+            anchor_spl::token::revoke(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    anchor_spl::token::Revoke {
+                        source: deposit_token_account.to_account_info(),
+                        authority: ctx.accounts.borrower.to_account_info(),
+                    }
+                )
+            )?;
+
+            delegate_and_freeze_token_account(
+                token_manager,
+                ctx.accounts.token_program.to_account_info(),
+                deposit_token_account.to_account_info(),
+                ctx.accounts.borrower.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.edition.to_account_info(),
+                ctx.accounts.borrower.to_account_info(),
+            )?;
+        } else if deposit_token_account.delegate.unwrap() != token_manager.key() || deposit_token_account.delegated_amount != 1 {
+            return err!(DexloanError::InvalidDelegate);
+        }
+    } else {
+        delegate_and_freeze_token_account(
+            token_manager,
+            ctx.accounts.token_program.to_account_info(),
+            deposit_token_account.to_account_info(),
+            ctx.accounts.borrower.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.edition.to_account_info(),
+            ctx.accounts.borrower.to_account_info(),
+        )?;
+    }
+
+    msg!("Paying {} lamports to borrower from collection loan offer escrow", loan.amount);
+
+    transfer_from_escrow(
+        &mut collection_loan_offer.to_account_info(),
+        &mut ctx.accounts.borrower.to_account_info(),
+        loan.amount,
+    )?;
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,57 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Mint};
+use crate::state::{CollectionLoanOffer, LoanOffer};
+
+#[derive(Accounts)]
+pub struct CancelLoanOffer<'info> {
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    /// The escrowed amount is refunded along with the rent
+    #[account(
+        mut,
+        seeds = [
+            LoanOffer::PREFIX,
+            mint.key().as_ref(),
+            lender.key().as_ref(),
+        ],
+        bump = loan_offer.bump,
+        has_one = lender,
+        has_one = mint,
+        close = lender
+    )]
+    pub loan_offer: Box<Account<'info, LoanOffer>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_cancel_loan_offer(_ctx: Context<CancelLoanOffer>) -> Result<()> {
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelCollectionLoanOffer<'info> {
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    /// The remaining escrowed amount is refunded along with the rent
+    #[account(
+        mut,
+        seeds = [
+            CollectionLoanOffer::PREFIX,
+            collection.key().as_ref(),
+            lender.key().as_ref(),
+        ],
+        bump = collection_loan_offer.bump,
+        has_one = lender,
+        has_one = collection,
+        close = lender
+    )]
+    pub collection_loan_offer: Box<Account<'info, CollectionLoanOffer>>,
+    pub collection: Box<Account<'info, Mint>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handle_cancel_collection_loan_offer(_ctx: Context<CancelCollectionLoanOffer>) -> Result<()> {
+    Ok(())
+}
\ No newline at end of file
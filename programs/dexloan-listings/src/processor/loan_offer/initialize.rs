@@ -0,0 +1,183 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Mint};
+use crate::state::{CollectionLoanOffer, InterestMode, LoanDefaultMode, LoanOffer};
+use crate::error::*;
+use crate::processor::loan::LoanArgs;
+
+// Offers are accepted as full term loans in lamports, so their terms are
+// validated as such a listing would be
+fn validate_offer_terms(amount: u64, basis_points: u32, duration: i64) -> Result<()> {
+    LoanArgs {
+        amount,
+        basis_points,
+        duration,
+        interest_mode: InterestMode::FullTerm,
+        minimum_fee: 0,
+        currency_mint: None,
+        liquidation_threshold: None,
+        price_feed: None,
+        grace_period: 0,
+        late_fee_basis_points: 0,
+        default_mode: LoanDefaultMode::Repossess,
+        notice_period: None,
+        listing_expiry: None,
+        counterparty: None,
+    }.validate()
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LoanOfferArgs {
+    amount: u64,
+    basis_points: u32,
+    duration: i64,
+    expiry: i64,
+}
+
+#[derive(Accounts)]
+pub struct InitLoanOffer<'info> {
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    #[account(
+        init,
+        payer = lender,
+        seeds = [
+            LoanOffer::PREFIX,
+            mint.key().as_ref(),
+            lender.key().as_ref(),
+        ],
+        space = LoanOffer::space(),
+        bump,
+    )]
+    pub loan_offer: Box<Account<'info, LoanOffer>>,
+    #[account(constraint = mint.supply == 1)]
+    pub mint: Box<Account<'info, Mint>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_init_loan_offer(
+  ctx: Context<InitLoanOffer>,
+  args: LoanOfferArgs,
+) -> Result<()> {
+    let loan_offer = &mut ctx.accounts.loan_offer;
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    if unix_timestamp > args.expiry {
+        return err!(DexloanError::InvalidExpiry)
+    }
+
+    if args.amount == 0 {
+        return err!(DexloanError::InvalidAmount)
+    }
+
+    validate_offer_terms(args.amount, args.basis_points, args.duration)?;
+
+    // Init
+    loan_offer.lender = ctx.accounts.lender.key();
+    loan_offer.mint = ctx.accounts.mint.key();
+    loan_offer.bump = *ctx.bumps.get("loan_offer").unwrap();
+    //
+    loan_offer.amount = args.amount;
+    loan_offer.basis_points = args.basis_points;
+    loan_offer.duration = args.duration;
+    loan_offer.expiry = args.expiry;
+
+    msg!("Paying {} lamports to loan offer escrow", args.amount);
+
+    anchor_lang::solana_program::program::invoke(
+        &anchor_lang::solana_program::system_instruction::transfer(
+            &loan_offer.lender,
+            &loan_offer.key(),
+            args.amount,
+        ),
+        &[
+            ctx.accounts.lender.to_account_info(),
+            loan_offer.to_account_info(),
+        ]
+    )?;
+
+    Ok(())
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CollectionLoanOfferArgs {
+    amount: u64,
+    basis_points: u32,
+    duration: i64,
+    expiry: i64,
+    count: u16,
+}
+
+#[derive(Accounts)]
+pub struct InitCollectionLoanOffer<'info> {
+    #[account(mut)]
+    pub lender: Signer<'info>,
+    #[account(
+        init,
+        payer = lender,
+        seeds = [
+            CollectionLoanOffer::PREFIX,
+            collection.key().as_ref(),
+            lender.key().as_ref(),
+        ],
+        space = CollectionLoanOffer::space(),
+        bump,
+    )]
+    pub collection_loan_offer: Box<Account<'info, CollectionLoanOffer>>,
+    pub collection: Box<Account<'info, Mint>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_init_collection_loan_offer(
+  ctx: Context<InitCollectionLoanOffer>,
+  args: CollectionLoanOfferArgs,
+) -> Result<()> {
+    let collection_loan_offer = &mut ctx.accounts.collection_loan_offer;
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    if unix_timestamp > args.expiry {
+        return err!(DexloanError::InvalidExpiry)
+    }
+
+    if args.amount == 0 || args.count == 0 {
+        return err!(DexloanError::InvalidAmount)
+    }
+
+    validate_offer_terms(args.amount, args.basis_points, args.duration)?;
+
+    // Init
+    collection_loan_offer.lender = ctx.accounts.lender.key();
+    collection_loan_offer.collection = ctx.accounts.collection.key();
+    collection_loan_offer.bump = *ctx.bumps.get("collection_loan_offer").unwrap();
+    //
+    collection_loan_offer.amount = args.amount;
+    collection_loan_offer.basis_points = args.basis_points;
+    collection_loan_offer.duration = args.duration;
+    collection_loan_offer.expiry = args.expiry;
+    collection_loan_offer.count = args.count;
+
+    let escrow_amount = args.amount
+        .checked_mul(u64::from(args.count))
+        .ok_or(DexloanError::NumericalOverflow)?;
+
+    msg!("Paying {} lamports to collection loan offer escrow", escrow_amount);
+
+    anchor_lang::solana_program::program::invoke(
+        &anchor_lang::solana_program::system_instruction::transfer(
+            &collection_loan_offer.lender,
+            &collection_loan_offer.key(),
+            escrow_amount,
+        ),
+        &[
+            ctx.accounts.lender.to_account_info(),
+            collection_loan_offer.to_account_info(),
+        ]
+    )?;
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,7 @@
+pub mod accept;
+pub mod cancel;
+pub mod initialize;
+
+pub use accept::*;
+pub use cancel::*;
+pub use initialize::*;
\ No newline at end of file
@@ -1,7 +1,17 @@
+pub mod bundle_loan;
 pub mod call_option;
 pub mod hire;
+pub mod listing;
 pub mod loan;
+pub mod loan_offer;
+pub mod protocol_config;
+pub mod put_option;
 
+pub use bundle_loan::*;
 pub use call_option::*;
 pub use hire::*;
-pub use loan::*;
\ No newline at end of file
+pub use listing::*;
+pub use loan::*;
+pub use loan_offer::*;
+pub use protocol_config::*;
+pub use put_option::*;
\ No newline at end of file
@@ -0,0 +1,74 @@
+use anchor_lang::{prelude::*};
+use crate::state::{ProtocolConfig};
+use crate::error::{DexloanError};
+use crate::program::DexloanListings;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProtocolConfigArgs {
+    pub treasury: Pubkey,
+    pub loan_fee_basis_points: u16,
+    pub option_premium_fee_basis_points: u16,
+    pub option_exercise_fee_basis_points: u16,
+    pub hire_fee_basis_points: u16,
+    pub oracle_program: Pubkey,
+}
+
+impl ProtocolConfigArgs {
+    pub fn validate(&self) -> Result<()> {
+        let fees = [
+            self.loan_fee_basis_points,
+            self.option_premium_fee_basis_points,
+            self.option_exercise_fee_basis_points,
+            self.hire_fee_basis_points,
+        ];
+
+        if fees.iter().any(|fee| *fee > 10_000) {
+            return err!(DexloanError::InvalidBasisPoints);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitProtocolConfig<'info> {
+    /// Only the program's upgrade authority may initialize the config
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, DexloanListings>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()))]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(
+        init,
+        payer = admin,
+        seeds = [ProtocolConfig::PREFIX],
+        space = ProtocolConfig::space(),
+        bump,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_init_protocol_config(
+    ctx: Context<InitProtocolConfig>,
+    args: ProtocolConfigArgs,
+) -> Result<()> {
+    let protocol_config = &mut ctx.accounts.protocol_config;
+
+    args.validate()?;
+
+    protocol_config.admin = ctx.accounts.admin.key();
+    protocol_config.bump = *ctx.bumps.get("protocol_config").unwrap();
+    //
+    protocol_config.treasury = args.treasury;
+    protocol_config.loan_fee_basis_points = args.loan_fee_basis_points;
+    protocol_config.option_premium_fee_basis_points = args.option_premium_fee_basis_points;
+    protocol_config.option_exercise_fee_basis_points = args.option_exercise_fee_basis_points;
+    protocol_config.hire_fee_basis_points = args.hire_fee_basis_points;
+    protocol_config.oracle_program = args.oracle_program;
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,5 @@
+pub mod initialize;
+pub mod update;
+
+pub use initialize::*;
+pub use update::*;
\ No newline at end of file
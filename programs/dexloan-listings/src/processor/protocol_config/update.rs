@@ -0,0 +1,55 @@
+use anchor_lang::{prelude::*};
+use crate::state::{ProtocolConfig};
+use crate::processor::protocol_config::ProtocolConfigArgs;
+
+#[derive(Accounts)]
+pub struct UpdateProtocolConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = admin,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+}
+
+pub fn handle_update_protocol_config(
+    ctx: Context<UpdateProtocolConfig>,
+    args: ProtocolConfigArgs,
+) -> Result<()> {
+    let protocol_config = &mut ctx.accounts.protocol_config;
+
+    args.validate()?;
+
+    protocol_config.treasury = args.treasury;
+    protocol_config.loan_fee_basis_points = args.loan_fee_basis_points;
+    protocol_config.option_premium_fee_basis_points = args.option_premium_fee_basis_points;
+    protocol_config.option_exercise_fee_basis_points = args.option_exercise_fee_basis_points;
+    protocol_config.hire_fee_basis_points = args.hire_fee_basis_points;
+    protocol_config.oracle_program = args.oracle_program;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolAdmin<'info> {
+    pub admin: Signer<'info>,
+    /// The new admin must sign to prevent rotating to an inaccessible key
+    pub new_admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = admin,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+}
+
+pub fn handle_set_protocol_admin(ctx: Context<SetProtocolAdmin>) -> Result<()> {
+    let protocol_config = &mut ctx.accounts.protocol_config;
+
+    protocol_config.admin = ctx.accounts.new_admin.key();
+
+    Ok(())
+}
\ No newline at end of file
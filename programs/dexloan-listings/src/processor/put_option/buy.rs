@@ -0,0 +1,90 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Mint};
+use crate::state::{PutOption, PutOptionState, ProtocolConfig};
+use crate::error::{DexloanError};
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct BuyPutOption<'info> {
+    /// CHECK: contrained on put_option
+    #[account(mut)]
+    pub writer: AccountInfo<'info>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PutOption::PREFIX,
+            mint.key().as_ref(),
+            writer.key().as_ref(),
+        ],
+        bump,
+        has_one = writer,
+        has_one = mint,
+        constraint = put_option.writer != buyer.key(),
+        constraint = put_option.state == PutOptionState::Listed,
+    )]
+    pub put_option: Box<Account<'info, PutOption>>,
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_buy_put_option(
+    ctx: Context<BuyPutOption>,
+    expected_strike_price: u64,
+    expected_premium: u64,
+    expected_expiry: i64,
+) -> Result<()> {
+    let put_option = &mut ctx.accounts.put_option;
+
+    if ctx.accounts.clock.unix_timestamp > put_option.expiry {
+        return Err(DexloanError::OptionExpired.into())
+    }
+
+    // Guards against the writer changing the terms before the transaction lands
+    if put_option.strike_price != expected_strike_price
+        || put_option.amount != expected_premium
+        || put_option.expiry != expected_expiry
+    {
+        return err!(DexloanError::TermsMismatch);
+    }
+
+    put_option.state = PutOptionState::Active;
+    put_option.buyer = ctx.accounts.buyer.key();
+
+    let protocol_fee = calculate_fee_from_basis_points(
+        put_option.amount as u128,
+        ctx.accounts.protocol_config.option_premium_fee_basis_points as u128,
+    )?;
+
+    // Transfer option premium
+    transfer_currency(
+        &None,
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.writer.to_account_info(),
+        put_option.amount - protocol_fee,
+    )?;
+    transfer_currency(
+        &None,
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.treasury.to_account_info(),
+        protocol_fee,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint};
+use crate::state::{PutOption, PutOptionState};
+use crate::error::{DexloanError};
+
+#[derive(Accounts)]
+pub struct ClosePutOption<'info> {
+    #[account(mut)]
+    pub writer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PutOption::PREFIX,
+            mint.key().as_ref(),
+            writer.key().as_ref(),
+        ],
+        bump,
+        has_one = writer,
+        has_one = mint,
+        close = writer
+    )]
+    pub put_option: Box<Account<'info, PutOption>>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+pub fn handle_close_put_option(ctx: Context<ClosePutOption>) -> Result<()> {
+    let put_option = &ctx.accounts.put_option;
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    // Closing refunds any escrowed strike to the writer
+    if put_option.state == PutOptionState::Active && put_option.expiry > unix_timestamp {
+        return Err(DexloanError::OptionNotExpired.into())
+    }
+
+    Ok(())
+}
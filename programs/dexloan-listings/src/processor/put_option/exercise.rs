@@ -0,0 +1,125 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount},
+};
+use crate::state::{PutOption, PutOptionState, ProtocolConfig};
+use crate::error::{DexloanError};
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct ExercisePutOption<'info> {
+    /// CHECK: contrained on put_option
+    #[account(mut)]
+    pub writer: AccountInfo<'info>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PutOption::PREFIX,
+            mint.key().as_ref(),
+            writer.key().as_ref(),
+        ],
+        bump,
+        has_one = writer,
+        has_one = buyer,
+        has_one = mint,
+        constraint = put_option.state == PutOptionState::Active,
+    )]
+    pub put_option: Box<Account<'info, PutOption>>,
+    #[account(
+        mut,
+        constraint = buyer_token_account.amount == 1,
+        associated_token::mint = mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = writer,
+    )]
+    pub writer_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        seeds = [ProtocolConfig::PREFIX],
+        bump = protocol_config.bump,
+        has_one = treasury,
+    )]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// CHECK: contrained on protocol_config
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+    pub mint: Box<Account<'info, Mint>>,
+    /// CHECK: deserialized and checked
+    pub metadata: UncheckedAccount<'info>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_exercise_put_option<'info>(ctx: Context<'_, '_, '_, 'info, ExercisePutOption<'info>>) -> Result<()> {
+    let put_option = &mut ctx.accounts.put_option;
+    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    msg!("Exercise with strike price: {} lamports", put_option.strike_price);
+
+    if unix_timestamp > put_option.expiry {
+        return Err(DexloanError::OptionExpired.into())
+    }
+
+    put_option.state = PutOptionState::Exercised;
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.buyer_token_account.to_account_info(),
+                to: ctx.accounts.writer_token_account.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            }
+        ),
+        1
+    )?;
+
+    // The strike is released to the buyer, who then pays royalties and fees from it
+    transfer_from_escrow(
+        &mut put_option.to_account_info(),
+        &mut ctx.accounts.buyer.to_account_info(),
+        put_option.strike_price,
+    )?;
+
+    let remaining_amount = pay_creator_fees(
+        remaining_accounts,
+        put_option.strike_price,
+        &ctx.accounts.mint.to_account_info(),
+        &ctx.accounts.metadata.to_account_info(),
+        &None,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+    )?;
+
+    msg!("paid to creators {}", put_option.strike_price - remaining_amount);
+
+    let protocol_fee = calculate_fee_from_basis_points(
+        put_option.strike_price as u128,
+        ctx.accounts.protocol_config.option_exercise_fee_basis_points as u128,
+    )?;
+
+    transfer_currency(
+        &None,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.treasury.to_account_info(),
+        protocol_fee,
+    )?;
+
+    Ok(())
+}
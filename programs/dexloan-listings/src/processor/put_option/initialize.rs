@@ -0,0 +1,72 @@
+use anchor_lang::{prelude::*};
+use anchor_spl::token::{Mint};
+use crate::state::{PutOption, PutOptionState};
+use crate::error::{DexloanError};
+
+#[derive(Accounts)]
+pub struct InitPutOption<'info> {
+    #[account(mut)]
+    pub writer: Signer<'info>,
+    #[account(
+        init,
+        payer = writer,
+        seeds = [
+            PutOption::PREFIX,
+            mint.key().as_ref(),
+            writer.key().as_ref(),
+        ],
+        space = PutOption::space(),
+        bump,
+    )]
+    pub put_option: Box<Account<'info, PutOption>>,
+    #[account(constraint = mint.supply == 1)]
+    pub mint: Box<Account<'info, Mint>>,
+    /// Misc
+    pub system_program: Program<'info, System>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handle_init_put_option(
+    ctx: Context<InitPutOption>,
+    amount: u64,
+    strike_price: u64,
+    expiry: i64
+) -> Result<()> {
+    let put_option = &mut ctx.accounts.put_option;
+    let unix_timestamp = ctx.accounts.clock.unix_timestamp;
+
+    if unix_timestamp > expiry {
+        return Err(DexloanError::InvalidExpiry.into())
+    }
+
+    if strike_price == 0 {
+        return Err(DexloanError::InvalidAmount.into())
+    }
+
+    // Init
+    put_option.writer = ctx.accounts.writer.key();
+    put_option.mint = ctx.accounts.mint.key();
+    put_option.bump = *ctx.bumps.get("put_option").unwrap();
+    //
+    put_option.amount = amount;
+    put_option.expiry = expiry;
+    put_option.strike_price = strike_price;
+    put_option.state = PutOptionState::Listed;
+
+    msg!("Paying {} lamports to put option escrow", strike_price);
+
+    anchor_lang::solana_program::program::invoke(
+        &anchor_lang::solana_program::system_instruction::transfer(
+            &put_option.writer,
+            &put_option.key(),
+            strike_price,
+        ),
+        &[
+            ctx.accounts.writer.to_account_info(),
+            put_option.to_account_info(),
+        ]
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,9 @@
+pub mod buy;
+pub mod close;
+pub mod exercise;
+pub mod initialize;
+
+pub use buy::*;
+pub use close::*;
+pub use exercise::*;
+pub use initialize::*;
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use crate::state::LoanState;
+
+#[account]
+pub struct BundleLoan {
+    /// Chosen by the borrower to derive the bundle's address
+    pub id: u64,
+    /// Whether the loan is active
+    pub state: LoanState,
+    /// The amount of the loan
+    pub amount: u64,
+    /// The NFT holder
+    pub borrower: Pubkey,
+    /// The issuer of the loan
+    pub lender: Pubkey,
+    /// Annualized return
+    pub basis_points: u32,
+    /// Duration of the loan in seconds
+    pub duration: i64,
+    /// The start date of the loan
+    pub start_date: i64,
+    /// The NFTs held as collateral
+    pub mints: Vec<Pubkey>,
+    /// Misc
+    pub bump: u8,
+}
+
+impl BundleLoan {
+    pub fn space() -> usize {
+        8 + // key
+        8 + // id
+        1 + // state
+        8 + // amount
+        32 + // borrower
+        32 + // lender
+        4 + // basis_points
+        8 + // duration
+        8 + // start_date
+        (4 + 32 * BundleLoan::MAX_MINTS) + // mints
+        1 // bump
+    }
+
+    pub const PREFIX: &'static [u8] = b"bundle_loan";
+    pub const MAX_MINTS: usize = 5;
+}
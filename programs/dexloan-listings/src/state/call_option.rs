@@ -1,10 +1,60 @@
 use anchor_lang::prelude::*;
+use crate::error::{DexloanError};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
 pub enum CallOptionState {
     Listed,
     Active,
     Exercised,
+    Auctioning,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct ExerciseWindow {
+    pub start: i64,
+    pub end: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ExerciseStyle {
+    /// Exercisable at any time before expiry
+    American,
+    /// Only exercisable within `window` seconds before expiry
+    European { window: i64 },
+    /// Only exercisable within one of the given windows
+    Bermudan { windows: Vec<ExerciseWindow> },
+}
+
+impl ExerciseStyle {
+    pub const MAX_WINDOWS: usize = 4;
+
+    pub fn space() -> usize {
+        1 + // variant
+        4 + // vec length
+        (8 + 8) * Self::MAX_WINDOWS // windows
+    }
+
+    pub fn validate(&self, expiry: i64) -> Result<()> {
+        match self {
+            ExerciseStyle::American => {}
+            ExerciseStyle::European { window } => {
+                require_gt!(*window, 0, DexloanError::InvalidExpiry);
+            }
+            ExerciseStyle::Bermudan { windows } => {
+                if windows.is_empty() || windows.len() > Self::MAX_WINDOWS {
+                    return err!(DexloanError::InvalidExpiry);
+                }
+
+                for window in windows {
+                    if window.start > window.end || window.end > expiry {
+                        return err!(DexloanError::InvalidExpiry);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[account]
@@ -26,10 +76,47 @@ pub struct CallOption {
     /// Misc
     pub padding: [u8; 64],
     pub bump: u8,
+    // Fields below were added after the original layout and are only present
+    // on accounts created since, or extended with `migrate_call_option`
+    /// The date after which the listing can no longer be bought
+    pub listing_expiry: Option<i64>,
+    /// The only buyer allowed to buy a private listing
+    pub counterparty: Option<Pubkey>,
+    /// The price the buyer is asking to sell their position for
+    pub position_price: Option<u64>,
+    /// The price the buyer is asking the seller to cancel the option for
+    pub buy_back_price: Option<u64>,
+    /// The date bids on the premium close when auctioning
+    pub auction_end: i64,
+    /// The token premiums and the strike price are paid in, if not lamports
+    pub currency_mint: Option<Pubkey>,
+    /// When the option can be exercised
+    pub exercise_style: ExerciseStyle,
 }
 
 impl CallOption {
     pub fn space() -> usize {
+        8 + // key
+        1 + // state
+        8 + // amount
+        32 + // seller
+        32 + // buyer
+        8 + // expiry
+        8 + // strike price
+        32 + // mint
+        64 + // padding
+        1 + // bump
+        (1 + 8) + // listing_expiry
+        (1 + 32) + // counterparty
+        (1 + 8) + // position_price
+        (1 + 8) + // buy_back_price
+        8 + // auction_end
+        (1 + 32) + // currency_mint
+        ExerciseStyle::space() // exercise_style
+    }
+
+    // The size of accounts created before fields were added after the bump
+    pub fn legacy_space() -> usize {
         8 + // key
         1 + // state
         8 + // amount
@@ -42,5 +129,45 @@ impl CallOption {
         1 // bump
     }
 
+    pub fn is_listing_expired(&self, unix_timestamp: i64) -> bool {
+        matches!(self.listing_expiry, Some(listing_expiry) if unix_timestamp > listing_expiry)
+    }
+
+    pub fn is_exercisable(&self, unix_timestamp: i64) -> bool {
+        if unix_timestamp > self.expiry {
+            return false;
+        }
+
+        match &self.exercise_style {
+            ExerciseStyle::American => true,
+            ExerciseStyle::European { window } => unix_timestamp >= self.expiry - window,
+            ExerciseStyle::Bermudan { windows } => windows.iter().any(
+                |window| unix_timestamp >= window.start && unix_timestamp <= window.end
+            ),
+        }
+    }
+
     pub const PREFIX: &'static [u8] = b"call_option";
 }
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn legacy_call_options_keep_their_bump_once_migrated() {
+        let mut data = vec![0; CallOption::legacy_space()];
+        data[..8].copy_from_slice(&CallOption::discriminator());
+        data[CallOption::legacy_space() - 1] = 254;
+        data.resize(CallOption::space(), 0);
+
+        let call_option = CallOption::try_deserialize(&mut &data[..]).unwrap();
+
+        assert_eq!(call_option.bump, 254);
+        assert!(matches!(call_option.exercise_style, ExerciseStyle::American));
+        assert_eq!(call_option.currency_mint, None);
+        assert_eq!(call_option.listing_expiry, None);
+    }
+}
\ No newline at end of file
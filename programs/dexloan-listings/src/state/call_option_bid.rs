@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+/// Escrows the highest premium bid on an auctioning call option
+#[account]
+pub struct CallOptionBid {
+    /// The call option being auctioned
+    pub call_option: Pubkey,
+    /// The highest bidder, if any
+    pub bidder: Option<Pubkey>,
+    /// The highest bid in lamports, held by this account
+    pub amount: u64,
+    /// Misc
+    pub bump: u8,
+}
+
+impl CallOptionBid {
+    pub fn space() -> usize {
+        8 + // key
+        32 + // call_option
+        (1 + 32) + // bidder
+        8 + // amount
+        1 // bump
+    }
+
+    pub const PREFIX: &'static [u8] = b"call_option_bid";
+}
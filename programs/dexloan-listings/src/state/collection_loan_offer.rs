@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct CollectionLoanOffer {
+    /// The amount of each loan, held in escrow by this account
+    pub amount: u64,
+    /// The issuer of the offer
+    pub lender: Pubkey,
+    /// Annualized return
+    pub basis_points: u32,
+    /// Duration of each loan in seconds
+    pub duration: i64,
+    /// The date after which the offer can no longer be accepted
+    pub expiry: i64,
+    /// The verified collection the offer is made against
+    pub collection: Pubkey,
+    /// The number of loans remaining
+    pub count: u16,
+    /// Misc
+    pub bump: u8,
+}
+
+impl CollectionLoanOffer {
+    pub fn space() -> usize {
+        8 + // key
+        8 + // amount
+        32 + // lender
+        4 + // basis_points
+        8 + // duration
+        8 + // expiry
+        32 + // collection
+        2 + // count
+        1 // bump
+    }
+
+    pub const PREFIX: &'static [u8] = b"collection_loan_offer";
+}
\ No newline at end of file
@@ -5,6 +5,31 @@ pub enum LoanState {
     Listed,
     Active,
     Defaulted,
+    Repaid,
+    Auctioning,
+    Recalled,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum InterestMode {
+    /// Interest is charged for the full duration
+    FullTerm,
+    /// Interest is charged for the seconds elapsed since the start date
+    ProRata,
+    /// Pro-rata interest, but never less than the minimum fee
+    ProRataWithMinimum,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum LoanDefaultMode {
+    /// The lender repossesses the NFT once the loan is overdue
+    Repossess,
+    /// The NFT is sold in a Dutch auction once the loan is overdue
+    Auction {
+        start_price: u64,
+        floor_price: u64,
+        duration: i64,
+    },
 }
 
 #[account]
@@ -25,13 +50,84 @@ pub struct Loan {
     pub start_date: i64,
     /// The mint of the token being used for collateral
     pub mint: Pubkey,
+    /// How interest is calculated on repayment
+    pub interest_mode: InterestMode,
+    /// The minimum interest fee when pro-rata with minimum
+    pub minimum_fee: u64,
+    /// Interest accrued but not yet repaid
+    pub outstanding_interest: u64,
+    /// Interest repaid through partial repayments
+    pub interest_paid: u64,
+    /// The date interest was last accrued until
+    pub accrued_at: i64,
     /// Misc
-    pub padding: [u8; 64],
+    pub padding: [u8; 31],
     pub bump: u8,
+    // Fields below were added after the original layout and are only present
+    // on accounts created since, or extended with `migrate_loan`
+    /// The SPL token the loan is denominated in, if not native lamports
+    pub currency_mint: Option<Pubkey>,
+    /// The loan to value ratio in basis points at which the loan may be liquidated
+    pub liquidation_threshold: Option<u32>,
+    /// The account providing the collection floor price for liquidations
+    pub price_feed: Option<Pubkey>,
+    /// Seconds after maturity before the loan can be repossessed
+    pub grace_period: i64,
+    /// Late fee charged per day overdue, in basis points of the principal
+    pub late_fee_basis_points: u32,
+    /// Late fees already added to the outstanding interest
+    pub late_fees_charged: u64,
+    /// The 1/1 mint whose holder is treated as the lender, if issued
+    pub note_mint: Option<Pubkey>,
+    /// What happens to the NFT when the loan defaults
+    pub default_mode: LoanDefaultMode,
+    /// The date the default auction started
+    pub auction_start: i64,
+    /// Notice given to repay a callable loan once recalled, in seconds
+    pub notice_period: Option<i64>,
+    /// The date a callable loan was recalled by the lender
+    pub recalled_at: i64,
+    /// The date after which the listing can no longer be funded
+    pub listing_expiry: Option<i64>,
+    /// The only lender allowed to fund a private listing
+    pub counterparty: Option<Pubkey>,
 }
 
 impl Loan {
     pub fn space() -> usize {
+        8 + // key
+        1 + // state
+        8 + // amount
+        32 + // borrower
+        32 + // lender
+        4 + // basis_points
+        8 + // duration
+        8 + // start_date
+        32 + // mint
+        1 + // interest_mode
+        8 + // minimum_fee
+        8 + // outstanding_interest
+        8 + // interest_paid
+        8 + // accrued_at
+        31 + // padding
+        1 + // bump
+        (1 + 32) + // currency_mint
+        (1 + 4) + // liquidation_threshold
+        (1 + 32) + // price_feed
+        8 + // grace_period
+        4 + // late_fee_basis_points
+        8 + // late_fees_charged
+        (1 + 32) + // note_mint
+        (1 + 8 + 8 + 8) + // default_mode
+        8 + // auction_start
+        (1 + 8) + // notice_period
+        8 + // recalled_at
+        (1 + 8) + // listing_expiry
+        (1 + 32) // counterparty
+    }
+
+    // The size of accounts created before fields were added after the bump
+    pub fn legacy_space() -> usize {
         8 + // key
         1 + // state
         8 + // amount
@@ -45,5 +141,43 @@ impl Loan {
         1 // bump
     }
 
+    // Callable loans have no maturity until they are recalled
+    pub fn maturity(&self) -> Option<i64> {
+        match self.notice_period {
+            Some(notice_period) if self.state == LoanState::Recalled => {
+                self.recalled_at.checked_add(notice_period)
+            }
+            Some(_) => None,
+            None => self.start_date.checked_add(self.duration),
+        }
+    }
+
+    pub fn is_listing_expired(&self, unix_timestamp: i64) -> bool {
+        matches!(self.listing_expiry, Some(listing_expiry) if unix_timestamp > listing_expiry)
+    }
+
     pub const PREFIX: &'static [u8] = b"loan";
+    pub const NOTE_PREFIX: &'static [u8] = b"loan_note";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn legacy_loans_keep_their_bump_once_migrated() {
+        let mut data = vec![0; Loan::legacy_space()];
+        data[..8].copy_from_slice(&Loan::discriminator());
+        data[Loan::legacy_space() - 1] = 254;
+        data.resize(Loan::space(), 0);
+
+        let loan = Loan::try_deserialize(&mut &data[..]).unwrap();
+
+        assert_eq!(loan.bump, 254);
+        assert!(loan.interest_mode == InterestMode::FullTerm);
+        assert!(loan.default_mode == LoanDefaultMode::Repossess);
+        assert_eq!(loan.currency_mint, None);
+        assert_eq!(loan.notice_period, None);
+    }
 }
\ No newline at end of file
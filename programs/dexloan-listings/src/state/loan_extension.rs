@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct LoanExtension {
+    /// The proposed duration of the loan in seconds
+    pub duration: i64,
+    /// The proposed annualized return
+    pub basis_points: Option<u32>,
+    /// Whether accrued interest is capitalised into the loan amount
+    pub capitalise: bool,
+    /// The lender the extension was proposed to
+    pub lender: Pubkey,
+    /// The start date of the loan the extension was proposed for
+    pub start_date: i64,
+    /// Accrued interest escrowed until the lender accepts
+    pub escrow_balance: u64,
+    /// Misc
+    pub bump: u8,
+}
+
+impl LoanExtension {
+    pub fn space() -> usize {
+        8 + // key
+        8 + // duration
+        (1 + 4) + // basis_points
+        1 + // capitalise
+        32 + // lender
+        8 + // start_date
+        8 + // escrow_balance
+        1 // bump
+    }
+
+    pub const PREFIX: &'static [u8] = b"loan_extension";
+}
\ No newline at end of file
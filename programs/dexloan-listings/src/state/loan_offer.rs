@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct LoanOffer {
+    /// The amount of the loan, held in escrow by this account
+    pub amount: u64,
+    /// The issuer of the offer
+    pub lender: Pubkey,
+    /// Annualized return
+    pub basis_points: u32,
+    /// Duration of the loan in seconds
+    pub duration: i64,
+    /// The date after which the offer can no longer be accepted
+    pub expiry: i64,
+    /// The mint of the token the offer is made against
+    pub mint: Pubkey,
+    /// Misc
+    pub bump: u8,
+}
+
+impl LoanOffer {
+    pub fn space() -> usize {
+        8 + // key
+        8 + // amount
+        32 + // lender
+        4 + // basis_points
+        8 + // duration
+        8 + // expiry
+        32 + // mint
+        1 // bump
+    }
+
+    pub const PREFIX: &'static [u8] = b"loan_offer";
+}
\ No newline at end of file
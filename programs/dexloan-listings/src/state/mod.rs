@@ -1,9 +1,25 @@
+pub mod bundle_loan;
 pub mod call_option;
+pub mod call_option_bid;
+pub mod collection_loan_offer;
 pub mod loan;
+pub mod loan_extension;
+pub mod loan_offer;
 pub mod hire;
+pub mod price_feed;
+pub mod protocol_config;
+pub mod put_option;
 pub mod token_manager;
 
+pub use bundle_loan::*;
 pub use call_option::*;
+pub use call_option_bid::*;
+pub use collection_loan_offer::*;
 pub use loan::*;
+pub use loan_extension::*;
+pub use loan_offer::*;
 pub use hire::*;
+pub use price_feed::*;
+pub use protocol_config::*;
+pub use put_option::*;
 pub use token_manager::*;
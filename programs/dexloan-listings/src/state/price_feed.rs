@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+/// The layout expected of price accounts used for liquidations.
+/// Any oracle program can provide the floor price of a collection by
+/// writing this struct at the start of an account's data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PriceFeed {
+    /// The collection floor price in the loan's currency
+    pub price: u64,
+    /// The date the price was last updated
+    pub last_updated: i64,
+}
\ No newline at end of file
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct ProtocolConfig {
+    /// The authority permitted to update the config
+    pub admin: Pubkey,
+    /// The account protocol fees are paid to
+    pub treasury: Pubkey,
+    /// Fee on loan interest in basis points
+    pub loan_fee_basis_points: u16,
+    /// Fee on call option premiums in basis points
+    pub option_premium_fee_basis_points: u16,
+    /// Fee on call option strike prices in basis points
+    pub option_exercise_fee_basis_points: u16,
+    /// Fee on rental income in basis points
+    pub hire_fee_basis_points: u16,
+    /// The program that owns price feed accounts used for liquidations
+    pub oracle_program: Pubkey,
+    /// Misc
+    pub bump: u8,
+}
+
+impl ProtocolConfig {
+    pub fn space() -> usize {
+        8 + // key
+        32 + // admin
+        32 + // treasury
+        2 + // loan_fee_basis_points
+        2 + // option_premium_fee_basis_points
+        2 + // option_exercise_fee_basis_points
+        2 + // hire_fee_basis_points
+        32 + // oracle_program
+        1 // bump
+    }
+
+    pub const PREFIX: &'static [u8] = b"protocol_config";
+}
\ No newline at end of file
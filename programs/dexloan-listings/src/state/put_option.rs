@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum PutOptionState {
+    Listed,
+    Active,
+    Exercised,
+}
+
+#[account]
+pub struct PutOption {
+    /// Whether the option is active
+    pub state: PutOptionState,
+    /// The premium paid by the buyer
+    pub amount: u64,
+    /// The writer of the put option, who escrows the strike price
+    pub writer: Pubkey,
+    /// The buyer of the put option
+    pub buyer: Pubkey,
+    /// The date after which the option can no longer be exercised
+    pub expiry: i64,
+    /// The amount paid to the buyer on exercise, held in escrow by this account
+    pub strike_price: u64,
+    /// The mint of the NFT the option may be exercised with
+    pub mint: Pubkey,
+    /// Misc
+    pub bump: u8,
+}
+
+impl PutOption {
+    pub fn space() -> usize {
+        8 + // key
+        1 + // state
+        8 + // amount
+        32 + // writer
+        32 + // buyer
+        8 + // expiry
+        8 + // strike price
+        32 + // mint
+        1 // bump
+    }
+
+    pub const PREFIX: &'static [u8] = b"put_option";
+}
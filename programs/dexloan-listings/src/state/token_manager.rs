@@ -13,6 +13,9 @@ pub struct TokenManager {
     pub accounts: AccountState,
     /// Misc
     pub bump: u8,
+    /// Whether the token is held by a bundle loan, stored after the bump
+    /// so existing accounts read it from their unused trailing bytes
+    pub bundle: bool,
 }
 
 impl TokenManager {
@@ -24,6 +27,7 @@ impl TokenManager {
       32 + // mint
       (1 * 3) + // account state
       (1 + 8 + 32 + 32) + // escrow balance
-      1 // bump
+      1 + // bump
+      1 // bundle
   }
 }
\ No newline at end of file
@@ -4,15 +4,23 @@ use {
     prelude::*,
     solana_program::{
         program::{invoke, invoke_signed},
+        system_instruction,
     },
   },
   mpl_token_metadata::{
-    instruction::{freeze_delegated_account, thaw_delegated_account}
+    instruction::{freeze_delegated_account, thaw_delegated_account},
+    state::{TokenMetadataAccount},
   },
   metaplex_token_metadata::state::{Metadata}
 };
-use crate::state::{Hire, TokenManager};
+use crate::state::{AccountState, Hire, InterestMode, Loan, LoanDefaultMode, PriceFeed, ProtocolConfig, TokenManager};
+use anchor_spl::{
+    associated_token::get_associated_token_address,
+    token::{Mint, TokenAccount},
+};
 use crate::error::*;
+use crate::constants::*;
+use crate::math::*;
 
 pub struct FreezeParams<'a, 'b> {
   /// CHECK
@@ -238,22 +246,18 @@ pub fn calculate_widthdawl_amount<'info>(hire: &mut Account<'info, Hire>, unix_t
     require!(hire.current_start.is_some(), DexloanError::InvalidState);
     require!(hire.current_expiry.is_some(), DexloanError::InvalidState);
 
-    let start = hire.current_start.unwrap() as f64;
-    let end = hire.current_expiry.unwrap() as f64;
-    let now = unix_timestamp as f64;
-    let balance = hire.escrow_balance as f64;
+    let start = hire.current_start.unwrap();
+    let end = hire.current_expiry.unwrap();
 
-    if now > end {
+    if unix_timestamp >= end {
         return Ok(hire.escrow_balance)
     }
 
-    let fraction = (now - start) / (end - start);
-    let withdrawl_amount = balance * fraction;
-
-    Ok(withdrawl_amount.floor() as u64)
+    // Only the fees earned so far are withdrawn, in favour of the hire's borrower
+    calculate_pro_rata(hire.escrow_balance, start, end, unix_timestamp, Rounding::Down)
 }
 
-fn transfer_from_escrow(
+pub fn transfer_from_escrow(
     escrow: &mut AccountInfo,
     to: &mut AccountInfo,
     amount: u64,
@@ -275,19 +279,34 @@ pub fn withdraw_from_hire_escrow<'a, 'b>(
     hire: &mut Account<'a, Hire>,
     hire_escrow: &AccountInfo<'b>,
     lender: &AccountInfo<'b>,
+    protocol_config: &ProtocolConfig,
+    treasury: &AccountInfo<'b>,
     unix_timestamp: i64,
 ) -> Result<u64> {
     require_keys_eq!(lender.key(), hire.lender);
+    require_keys_eq!(treasury.key(), protocol_config.treasury);
 
     let amount = calculate_widthdawl_amount(hire, unix_timestamp)?;
+    let protocol_fee = calculate_fee_from_basis_points(
+        amount as u128,
+        protocol_config.hire_fee_basis_points as u128,
+    )?;
     msg!("Withdrawing {} lamports to lender from escrow balance ", amount);
 
     transfer_from_escrow(
         &mut hire_escrow.to_account_info(),
         &mut lender.to_account_info(),
-        amount
+        amount - protocol_fee
     )?;
 
+    if protocol_fee > 0 {
+        transfer_from_escrow(
+            &mut hire_escrow.to_account_info(),
+            &mut treasury.to_account_info(),
+            protocol_fee
+        )?;
+    }
+
     let remaining_amount = hire.escrow_balance - amount;
     hire.escrow_balance = remaining_amount;
     hire.current_start = Some(unix_timestamp);
@@ -302,12 +321,16 @@ pub fn settle_hire_escrow_balance<'a, 'b>(
     remaining_accounts: &mut Iter<AccountInfo<'b>>,
     hire_escrow: &AccountInfo<'b>,
     lender: &AccountInfo<'b>,
+    protocol_config: &ProtocolConfig,
+    treasury: &AccountInfo<'b>,
     unix_timestamp: i64,
 ) -> Result<()> {
     let remaining_escrow_balance = withdraw_from_hire_escrow(
         hire,
         &hire_escrow,
         &lender,
+        protocol_config,
+        treasury,
         unix_timestamp,
     )?;
 
@@ -378,24 +401,48 @@ pub fn assert_metadata_valid<'a>(
     Ok(())
 }
   
+pub fn assert_metadata_collection<'a>(
+    metadata_info: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    collection: &Pubkey,
+) -> Result<()> {
+    let metadata: mpl_token_metadata::state::Metadata =
+        mpl_token_metadata::state::Metadata::from_account_info(metadata_info)?;
+
+    if metadata.mint != mint.key() {
+        return err!(DexloanError::InvalidMint);
+    }
+
+    assert_metadata_valid(
+        metadata_info,
+        mint
+    )?;
+
+    match metadata.collection {
+        Some(metadata_collection) if metadata_collection.verified && metadata_collection.key == *collection => Ok(()),
+        _ => err!(DexloanError::InvalidCollection)
+    }
+}
+  
 pub fn calculate_fee_from_basis_points(
     amount: u128,
     basis_points: u128,
 ) -> Result<u64> {
-    let total_fee = basis_points.checked_mul(amount)
-        .ok_or(DexloanError::NumericalOverflow)?
-        .checked_div(10_000)
-        .ok_or(DexloanError::NumericalOverflow)? as u64;
-    
-    Ok(total_fee)
+    mul_div(amount, basis_points, BASIS_POINTS as u128, Rounding::Down)
 }
 
+// When a currency mint is set, royalties are paid from the payer's token account
+// and each creator account must be that creator's associated token account
+#[allow(clippy::too_many_arguments)]
 pub fn pay_creator_fees<'a>(
     remaining_accounts: &mut Iter<AccountInfo<'a>>,
     amount: u64,
     mint: &AccountInfo<'a>,
     metadata_info: &AccountInfo<'a>,
+    currency_mint: &Option<Pubkey>,
+    token_program: &AccountInfo<'a>,
     fee_payer: &AccountInfo<'a>,
+    from: &AccountInfo<'a>,
 ) -> Result<u64> {
     let metadata = Metadata::from_account_info(metadata_info)?;
 
@@ -415,35 +462,40 @@ pub fn pay_creator_fees<'a>(
             .checked_sub(total_fee)
             .ok_or(DexloanError::NumericalOverflow)?;
 
-    msg!("Paying {} lamports in royalties", total_fee);
+    msg!("Paying {} in royalties", total_fee);
         
     match metadata.data.creators {
         Some(creators) => {
             for creator in creators {
-                let pct = creator.share as u128;
-                let creator_fee = pct.checked_mul(total_fee as u128)
-                        .ok_or(DexloanError::NumericalOverflow)?
-                        .checked_div(100)
-                        .ok_or(DexloanError::NumericalOverflow)? as u64;
+                let creator_fee = mul_div(
+                    total_fee as u128,
+                    creator.share as u128,
+                    100,
+                    Rounding::Down
+                )?;
                 remaining_fee = remaining_fee
                         .checked_sub(creator_fee)
                         .ok_or(DexloanError::NumericalOverflow)?;
 
                 let current_creator_info = next_account_info(remaining_accounts)?;
                 msg!("current creator {}", current_creator_info.key());
-                if creator_fee > 0 {
-                    invoke(
-                        &anchor_lang::solana_program::system_instruction::transfer(
-                            &fee_payer.key(),
-                            &current_creator_info.key(),
-                            creator_fee,
-                        ),
-                        &[
-                            current_creator_info.to_account_info(),
-                            fee_payer.to_account_info(),
-                        ]
-                    )?;
+
+                if let Some(currency_mint) = currency_mint {
+                    require_keys_eq!(
+                        current_creator_info.key(),
+                        get_associated_token_address(&creator.address, currency_mint),
+                        DexloanError::DerivedKeyInvalid
+                    );
                 }
+
+                transfer_currency(
+                    currency_mint,
+                    token_program,
+                    fee_payer,
+                    from,
+                    current_creator_info,
+                    creator_fee,
+                )?;
             }
         }
         None => {
@@ -460,13 +512,450 @@ pub fn calculate_loan_repayment(
     basis_points: u32,
     duration: i64
 ) -> Result<u64> {
-    let annual_fee = calculate_fee_from_basis_points(amount as u128, basis_points as u128)?;
-    let fee_divisor = (31_536_000 as f64) / (duration as f64);
-    let pro_rata_fee = (annual_fee as f64 / fee_divisor).round() as u64;
+    // Interest is rounded up in favour of the lender
+    let pro_rata_fee = calculate_interest(amount, basis_points, duration, Rounding::Up)?;
     
-    msg!("annual interest fee {}", annual_fee);
-    msg!("fee_divisor {}", fee_divisor);
     msg!("pro_rata_fee {}", pro_rata_fee);
     
-    Ok(amount + pro_rata_fee)
+    Ok(amount.checked_add(pro_rata_fee).ok_or(DexloanError::NumericalOverflow)?)
+}
+
+// Interest accrued on the outstanding principal since the last accrual,
+// along with the date it has been accrued until
+fn calculate_accrued_interest(
+    loan: &Loan,
+    unix_timestamp: i64
+) -> Result<(u64, i64)> {
+    let accrued_from = loan.start_date.max(loan.accrued_at);
+    let accrued_until = match loan.interest_mode {
+        InterestMode::FullTerm => loan.start_date
+            .checked_add(loan.duration)
+            .ok_or(DexloanError::NumericalOverflow)?,
+        InterestMode::ProRata | InterestMode::ProRataWithMinimum => unix_timestamp,
+    };
+
+    if accrued_from >= accrued_until {
+        return Ok((0, accrued_from));
+    }
+
+    let elapsed = accrued_until - accrued_from;
+    let interest = calculate_loan_repayment(loan.amount, loan.basis_points, elapsed)?
+        .checked_sub(loan.amount)
+        .ok_or(DexloanError::NumericalOverflow)?;
+
+    msg!("accrued interest {} over {} seconds", interest, elapsed);
+
+    Ok((interest, accrued_until))
+}
+
+pub fn calculate_late_fee(
+    loan: &Loan,
+    unix_timestamp: i64
+) -> Result<u64> {
+    let maturity = match loan.maturity() {
+        Some(maturity) => maturity,
+        None => return Ok(0),
+    };
+
+    if unix_timestamp <= maturity || loan.late_fee_basis_points == 0 {
+        return Ok(0);
+    }
+
+    // Each day started after maturity is charged in full
+    let days_late = (unix_timestamp - maturity + SECONDS_PER_DAY - 1) / SECONDS_PER_DAY;
+    let daily_fee = calculate_fee_from_basis_points(
+        loan.amount as u128,
+        loan.late_fee_basis_points as u128
+    )?;
+    let late_fee = daily_fee
+        .checked_mul(days_late as u64)
+        .ok_or(DexloanError::NumericalOverflow)?;
+
+    Ok(late_fee)
+}
+
+pub fn accrue_loan_interest(
+    loan: &mut Loan,
+    unix_timestamp: i64
+) -> Result<()> {
+    let (interest, accrued_until) = calculate_accrued_interest(loan, unix_timestamp)?;
+    let late_fee = calculate_late_fee(loan, unix_timestamp)?;
+    let unpaid_late_fee = late_fee.saturating_sub(loan.late_fees_charged);
+
+    loan.outstanding_interest = loan.outstanding_interest
+        .checked_add(interest)
+        .ok_or(DexloanError::NumericalOverflow)?
+        .checked_add(unpaid_late_fee)
+        .ok_or(DexloanError::NumericalOverflow)?;
+    loan.late_fees_charged = loan.late_fees_charged.max(late_fee);
+    loan.accrued_at = accrued_until;
+
+    Ok(())
+}
+
+pub fn calculate_loan_amount_due(
+    loan: &Loan,
+    unix_timestamp: i64
+) -> Result<u64> {
+    let (interest, _) = calculate_accrued_interest(loan, unix_timestamp)?;
+    let mut interest_due = loan.outstanding_interest
+        .checked_add(interest)
+        .ok_or(DexloanError::NumericalOverflow)?;
+
+    if loan.interest_mode == InterestMode::ProRataWithMinimum {
+        let minimum_due = loan.minimum_fee.saturating_sub(loan.interest_paid);
+        interest_due = interest_due.max(minimum_due);
+    }
+
+    let late_fee = calculate_late_fee(loan, unix_timestamp)?;
+    interest_due = interest_due
+        .checked_add(late_fee.saturating_sub(loan.late_fees_charged))
+        .ok_or(DexloanError::NumericalOverflow)?;
+
+    let amount_due = loan.amount
+        .checked_add(interest_due)
+        .ok_or(DexloanError::NumericalOverflow)?;
+
+    Ok(amount_due)
+}
+
+// When a currency mint is set, each party's token account for that mint is
+// passed through remaining accounts, otherwise lamports move between wallets
+pub fn next_currency_account<'a>(
+    currency_mint: &Option<Pubkey>,
+    owner: &AccountInfo<'a>,
+    remaining_accounts: &mut Iter<AccountInfo<'a>>,
+) -> Result<AccountInfo<'a>> {
+    match currency_mint {
+        Some(currency_mint) => {
+            let token_account_info = next_account_info(remaining_accounts)?;
+            let token_account = Account::<TokenAccount>::try_from(token_account_info)?;
+
+            if token_account.mint != *currency_mint {
+                return err!(DexloanError::InvalidMint);
+            }
+
+            require_keys_eq!(token_account.owner, owner.key());
+
+            Ok(token_account_info.clone())
+        }
+        None => Ok(owner.clone())
+    }
+}
+
+pub fn transfer_currency<'a>(
+    currency_mint: &Option<Pubkey>,
+    token_program: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    from: &AccountInfo<'a>,
+    to: &AccountInfo<'a>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    match currency_mint {
+        Some(_) => {
+            anchor_spl::token::transfer(
+                CpiContext::new(
+                    token_program.clone(),
+                    anchor_spl::token::Transfer {
+                        from: from.clone(),
+                        to: to.clone(),
+                        authority: authority.clone(),
+                    }
+                ),
+                amount
+            )?;
+        }
+        None => {
+            invoke(
+                &anchor_lang::solana_program::system_instruction::transfer(
+                    &from.key(),
+                    &to.key(),
+                    amount,
+                ),
+                &[
+                    from.clone(),
+                    to.clone(),
+                ]
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn read_price_feed(
+    price_feed: &AccountInfo,
+    oracle_program: &Pubkey,
+    unix_timestamp: i64,
+) -> Result<u64> {
+    // Anyone could otherwise write a price in the expected layout
+    if price_feed.owner != oracle_program {
+        return err!(DexloanError::InvalidPriceFeed);
+    }
+
+    let data = price_feed.try_borrow_data()?;
+    let price_feed = PriceFeed::deserialize(&mut &data[..])?;
+
+    if unix_timestamp - price_feed.last_updated > PRICE_FEED_MAX_AGE {
+        return err!(DexloanError::StalePriceFeed);
+    }
+
+    Ok(price_feed.price)
+}
+
+pub fn assert_lender_position<'a>(
+    loan: &mut Loan,
+    lender: &AccountInfo<'a>,
+    remaining_accounts: &mut Iter<AccountInfo<'a>>,
+) -> Result<()> {
+    match loan.note_mint {
+        Some(note_mint) => {
+            // The holder of the loan note is treated as the lender
+            let note_account_info = next_account_info(remaining_accounts)?;
+            let note_account = Account::<TokenAccount>::try_from(note_account_info)?;
+
+            if note_account.mint != note_mint || note_account.owner != lender.key() || note_account.amount != 1 {
+                return err!(DexloanError::InvalidLoanNote);
+            }
+
+            loan.lender = lender.key();
+        }
+        None => require_keys_eq!(loan.lender, lender.key())
+    }
+
+    Ok(())
+}
+
+pub fn calculate_auction_end(loan: &Loan) -> Result<i64> {
+    match loan.default_mode {
+        LoanDefaultMode::Auction { duration, .. } => Ok(
+            loan.auction_start
+                .checked_add(duration)
+                .ok_or(DexloanError::NumericalOverflow)?
+        ),
+        LoanDefaultMode::Repossess => err!(DexloanError::InvalidState),
+    }
+}
+
+pub fn calculate_auction_price(
+    loan: &Loan,
+    unix_timestamp: i64
+) -> Result<u64> {
+    match loan.default_mode {
+        LoanDefaultMode::Auction { start_price, floor_price, .. } => {
+            // The price decays linearly from the start price to the floor
+            let discount = calculate_pro_rata(
+                start_price - floor_price,
+                loan.auction_start,
+                calculate_auction_end(loan)?,
+                unix_timestamp,
+                Rounding::Down
+            )?;
+
+            Ok(start_price - discount)
+        }
+        LoanDefaultMode::Repossess => err!(DexloanError::InvalidState),
+    }
+}
+
+pub struct BundleItem<'a> {
+    pub mint: AccountInfo<'a>,
+    pub edition: AccountInfo<'a>,
+    pub token_account: Account<'a, TokenAccount>,
+    pub token_manager: AccountInfo<'a>,
+}
+
+// Each NFT in a bundle is passed through remaining accounts as its mint,
+// edition, the owner's token account and its token manager
+pub fn next_bundle_item<'a>(
+    remaining_accounts: &mut Iter<AccountInfo<'a>>,
+    owner: &Pubkey,
+) -> Result<BundleItem<'a>> {
+    let mint = next_account_info(remaining_accounts)?;
+    let edition = next_account_info(remaining_accounts)?;
+    let token_account = Account::<TokenAccount>::try_from(next_account_info(remaining_accounts)?)?;
+    let token_manager = next_account_info(remaining_accounts)?;
+
+    if token_account.mint != mint.key() {
+        return err!(DexloanError::InvalidMint);
+    }
+
+    if token_account.owner != *owner || token_account.amount != 1 {
+        return err!(DexloanError::InvalidBundle);
+    }
+
+    Ok(BundleItem {
+        mint: mint.clone(),
+        edition: edition.clone(),
+        token_account,
+        token_manager: token_manager.clone(),
+    })
+}
+
+fn find_token_manager_address(
+    mint: &Pubkey,
+    issuer: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            TokenManager::PREFIX,
+            mint.as_ref(),
+            issuer.as_ref(),
+        ],
+        &crate::ID
+    )
+}
+
+pub fn load_token_manager<'a>(
+    token_manager_info: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    issuer: &AccountInfo<'a>,
+) -> Result<Account<'a, TokenManager>> {
+    let (address, _) = find_token_manager_address(&mint.key(), &issuer.key());
+
+    if token_manager_info.key() != address {
+        return err!(DexloanError::InvalidBundle);
+    }
+
+    Account::<TokenManager>::try_from(token_manager_info)
+}
+
+// Token managers passed through remaining accounts can't be created by
+// anchor's init_if_needed constraint and so are created here
+pub fn init_token_manager_if_needed<'a>(
+    token_manager_info: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    issuer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<Account<'a, TokenManager>> {
+    let mint_pubkey = mint.key();
+    let issuer_pubkey = issuer.key();
+    let (address, bump) = find_token_manager_address(&mint_pubkey, &issuer_pubkey);
+
+    if token_manager_info.key() != address {
+        return err!(DexloanError::InvalidBundle);
+    }
+
+    if token_manager_info.data_is_empty() {
+        let space = TokenManager::space();
+        let signer_bump = &[bump];
+        let signer_seeds = &[&[
+            TokenManager::PREFIX,
+            mint_pubkey.as_ref(),
+            issuer_pubkey.as_ref(),
+            signer_bump
+        ][..]];
+
+        invoke_signed(
+            &system_instruction::create_account(
+                &issuer_pubkey,
+                &address,
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                &crate::ID,
+            ),
+            &[
+                issuer.clone(),
+                token_manager_info.clone(),
+                system_program.clone(),
+            ],
+            signer_seeds
+        )?;
+
+        let token_manager = TokenManager {
+            accounts: AccountState {
+                loan: false,
+                call_option: false,
+                hire: false,
+            },
+            bump,
+            bundle: false,
+        };
+        token_manager.try_serialize(&mut &mut token_manager_info.try_borrow_mut_data()?[..])?;
+    }
+
+    Account::<TokenManager>::try_from(token_manager_info)
+}
+
+pub fn assert_bundle_mint(mint: &AccountInfo) -> Result<()> {
+    let mint_account = Account::<Mint>::try_from(mint)?;
+
+    if mint_account.supply != 1 {
+        return err!(DexloanError::InvalidMint);
+    }
+
+    Ok(())
+}
+
+pub fn thaw_and_revoke_bundle<'a>(
+    mints: &[Pubkey],
+    remaining_accounts: &mut Iter<AccountInfo<'a>>,
+    borrower: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+) -> Result<()> {
+    for mint in mints {
+        let item = next_bundle_item(remaining_accounts, &borrower.key())?;
+
+        if item.mint.key() != *mint {
+            return err!(DexloanError::InvalidMint);
+        }
+
+        let mut token_manager = load_token_manager(&item.token_manager, &item.mint, borrower)?;
+
+        token_manager.bundle = false;
+
+        thaw_and_revoke_token_account(
+            &mut token_manager,
+            token_program.clone(),
+            item.token_account.to_account_info(),
+            borrower.clone(),
+            item.mint.clone(),
+            item.edition.clone(),
+        )?;
+
+        token_manager.exit(&crate::ID)?;
+    }
+
+    Ok(())
+}
+
+// Extends an account created with an older, shorter layout so that it can be
+// deserialized with the current one. The new bytes are zeroed, which each
+// added field reads as its default.
+pub fn migrate_account<'a>(
+    account: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    discriminator: [u8; 8],
+    legacy_space: usize,
+    space: usize,
+) -> Result<()> {
+    {
+        let data = account.try_borrow_data()?;
+
+        if data.len() != legacy_space || data[..8] != discriminator {
+            return err!(DexloanError::InvalidState);
+        }
+    }
+
+    let rent = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(account.lamports());
+
+    transfer_currency(
+        &None,
+        system_program,
+        payer,
+        payer,
+        account,
+        rent,
+    )?;
+
+    account.realloc(space, true)?;
+
+    Ok(())
 }
\ No newline at end of file
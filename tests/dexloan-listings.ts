@@ -14,6 +14,12 @@ describe("dexloan_listings", () => {
     anchor.AnchorProvider.defaultOptions().preflightCommitment
   );
 
+  let admin: Awaited<ReturnType<typeof helpers.initProtocolConfig>>;
+
+  before(async () => {
+    admin = await helpers.initProtocolConfig(connection);
+  });
+
   describe("Loans", () => {
     describe("Loan repossessions", () => {
       let borrower: helpers.LoanBorrower;
@@ -335,7 +341,22 @@ describe("dexloan_listings", () => {
         const duration = new anchor.BN(options.duration);
 
         await borrower.program.methods
-          .initLoan(amount, basisPoints, duration)
+          .initLoan({
+            amount,
+            basisPoints,
+            duration,
+            interestMode: { fullTerm: {} },
+            minimumFee: new anchor.BN(0),
+            currencyMint: null,
+            liquidationThreshold: null,
+            priceFeed: null,
+            gracePeriod: new anchor.BN(0),
+            lateFeeBasisPoints: 0,
+            defaultMode: { repossess: {} },
+            noticePeriod: null,
+            listingExpiry: null,
+            counterparty: null,
+          })
           .accounts({
             loan: borrower.loanAccount,
             tokenManager: borrower.tokenManager,
@@ -439,94 +460,2321 @@ describe("dexloan_listings", () => {
             })
             .rpc();
 
-          assert.ok(false);
-        } catch (error) {
-          assert.ok(error.toString(), "This loan is not overdue");
-        }
+          assert.ok(false);
+        } catch (error) {
+          assert.ok(error.toString(), "This loan is not overdue");
+        }
+      });
+
+      it("Allows loans to be repaid", async () => {
+        const borrower = await helpers.initLoan(connection, options);
+        const lender = await helpers.giveLoan(connection, borrower);
+        const lenderPreRepaymentBalance = await connection.getBalance(
+          lender.keypair.publicKey
+        );
+
+        await borrower.program.methods
+          .repayLoan()
+          .accounts({
+            protocolConfig: await helpers.findProtocolConfigAddress(),
+            treasury: helpers.treasury.publicKey,
+            loan: borrower.loanAccount,
+            tokenManager: borrower.tokenManager,
+            borrower: borrower.keypair.publicKey,
+            depositTokenAccount: borrower.depositTokenAccount,
+            lender: lender.keypair.publicKey,
+            mint: borrower.mint,
+            edition: borrower.edition,
+            metadataProgram: METADATA_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            tokenProgram: splToken.TOKEN_PROGRAM_ID,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          })
+          .rpc();
+
+        const lenderPostRepaymentBalance = await connection.getBalance(
+          lender.keypair.publicKey
+        );
+        const borrowerTokenAccount = await splToken.getAccount(
+          connection,
+          borrower.depositTokenAccount
+        );
+        const tokenManager = await borrower.program.account.tokenManager.fetch(
+          borrower.tokenManager
+        );
+
+        assert.deepEqual(tokenManager.accounts, {
+          hire: false,
+          callOption: false,
+          loan: false,
+        });
+        assert.equal(borrowerTokenAccount.amount, BigInt(1));
+        assert.equal(borrowerTokenAccount.delegate, null);
+        assert(lenderPostRepaymentBalance > lenderPreRepaymentBalance);
+      });
+
+      it("Charges pro-rata interest on early repayment", async () => {
+        const borrower = await helpers.initLoan(connection, {
+          ...options,
+          interestMode: "proRata",
+        });
+        const lender = await helpers.giveLoan(connection, borrower);
+        const { startDate } = await borrower.program.account.loan.fetch(
+          borrower.loanAccount
+        );
+        const lenderPreRepaymentBalance = await connection.getBalance(
+          lender.keypair.publicKey
+        );
+
+        const signature = await borrower.program.methods
+          .repayLoan()
+          .accounts({
+            protocolConfig: await helpers.findProtocolConfigAddress(),
+            treasury: helpers.treasury.publicKey,
+            loan: borrower.loanAccount,
+            tokenManager: borrower.tokenManager,
+            borrower: borrower.keypair.publicKey,
+            depositTokenAccount: borrower.depositTokenAccount,
+            lender: lender.keypair.publicKey,
+            mint: borrower.mint,
+            edition: borrower.edition,
+            metadataProgram: METADATA_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            tokenProgram: splToken.TOKEN_PROGRAM_ID,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          })
+          .rpc({ commitment: "confirmed" });
+
+        const tx = await connection.getTransaction(signature, {
+          commitment: "confirmed",
+        });
+        const lenderPostRepaymentBalance = await connection.getBalance(
+          lender.keypair.publicKey
+        );
+        // Interest accrues until the repayment and is rounded up
+        const elapsed = new anchor.BN(tx.blockTime).sub(startDate);
+        const interest = new anchor.BN(options.amount)
+          .mul(new anchor.BN(options.basisPoints))
+          .mul(elapsed)
+          .add(new anchor.BN(10_000 * 31_536_000 - 1))
+          .div(new anchor.BN(10_000 * 31_536_000));
+
+        assert.equal(
+          lenderPostRepaymentBalance - lenderPreRepaymentBalance,
+          options.amount + interest.toNumber()
+        );
+      });
+
+      it("Charges the minimum fee when pro-rata interest is lower", async () => {
+        const minimumFee = options.amount / 10;
+        const borrower = await helpers.initLoan(connection, {
+          ...options,
+          interestMode: "proRataWithMinimum",
+          minimumFee,
+        });
+        const lender = await helpers.giveLoan(connection, borrower);
+        const lenderPreRepaymentBalance = await connection.getBalance(
+          lender.keypair.publicKey
+        );
+
+        await borrower.program.methods
+          .repayLoan()
+          .accounts({
+            protocolConfig: await helpers.findProtocolConfigAddress(),
+            treasury: helpers.treasury.publicKey,
+            loan: borrower.loanAccount,
+            tokenManager: borrower.tokenManager,
+            borrower: borrower.keypair.publicKey,
+            depositTokenAccount: borrower.depositTokenAccount,
+            lender: lender.keypair.publicKey,
+            mint: borrower.mint,
+            edition: borrower.edition,
+            metadataProgram: METADATA_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            tokenProgram: splToken.TOKEN_PROGRAM_ID,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          })
+          .rpc();
+
+        const lenderPostRepaymentBalance = await connection.getBalance(
+          lender.keypair.publicKey
+        );
+
+        assert.equal(
+          lenderPostRepaymentBalance - lenderPreRepaymentBalance,
+          options.amount + minimumFee
+        );
+      });
+
+      it("Allows loans to be repaid in installments", async () => {
+        const borrower = await helpers.initLoan(connection, options);
+        const lender = await helpers.giveLoan(connection, borrower);
+        const accounts = {
+          loan: borrower.loanAccount,
+          tokenManager: borrower.tokenManager,
+          borrower: borrower.keypair.publicKey,
+          depositTokenAccount: borrower.depositTokenAccount,
+          lender: lender.keypair.publicKey,
+          mint: borrower.mint,
+          edition: borrower.edition,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          protocolConfig: await helpers.findProtocolConfigAddress(),
+          treasury: helpers.treasury.publicKey,
+        };
+
+        const installment = new anchor.BN(options.amount / 2);
+        await borrower.program.methods
+          .repayLoanPartial(installment)
+          .accounts(accounts)
+          .rpc();
+
+        let loan = await borrower.program.account.loan.fetch(
+          borrower.loanAccount
+        );
+        let borrowerTokenAccount = await splToken.getAccount(
+          connection,
+          borrower.depositTokenAccount
+        );
+
+        assert.deepEqual(loan.state, { active: {} });
+        assert(loan.amount.toNumber() < options.amount);
+        assert.ok(borrowerTokenAccount.isFrozen);
+
+        await borrower.program.methods
+          .repayLoanPartial(new anchor.BN(options.amount))
+          .accounts(accounts)
+          .rpc();
+
+        loan = await borrower.program.account.loan.fetch(borrower.loanAccount);
+        borrowerTokenAccount = await splToken.getAccount(
+          connection,
+          borrower.depositTokenAccount
+        );
+        const tokenManager = await borrower.program.account.tokenManager.fetch(
+          borrower.tokenManager
+        );
+
+        assert.deepEqual(loan.state, { repaid: {} });
+        assert.equal(loan.amount.toNumber(), 0);
+        assert.deepEqual(tokenManager.accounts, {
+          hire: false,
+          callOption: false,
+          loan: false,
+        });
+        assert.equal(borrowerTokenAccount.delegate, null);
+        assert.ok(!borrowerTokenAccount.isFrozen);
+      });
+
+      it("Allows loans to be refinanced by a new lender", async () => {
+        const borrower = await helpers.initLoan(connection, options);
+        const lender = await helpers.giveLoan(connection, borrower);
+        const newLender = anchor.web3.Keypair.generate();
+        await helpers.requestAirdrop(connection, newLender.publicKey);
+
+        const lenderPreRefinanceBalance = await connection.getBalance(
+          lender.keypair.publicKey
+        );
+
+        await borrower.program.methods
+          .refinanceLoan({
+            amount: new anchor.BN(options.amount * 2),
+            basisPoints: new anchor.BN(options.basisPoints),
+            duration: new anchor.BN(options.duration),
+            interestMode: { fullTerm: {} },
+            minimumFee: new anchor.BN(0),
+            currencyMint: null,
+            liquidationThreshold: null,
+            priceFeed: null,
+            gracePeriod: new anchor.BN(0),
+            lateFeeBasisPoints: 0,
+            defaultMode: { repossess: {} },
+            noticePeriod: null,
+            listingExpiry: null,
+            counterparty: null,
+          })
+          .accounts({
+            loan: borrower.loanAccount,
+            borrower: borrower.keypair.publicKey,
+            lender: lender.keypair.publicKey,
+            newLender: newLender.publicKey,
+            mint: borrower.mint,
+            protocolConfig: await helpers.findProtocolConfigAddress(),
+            treasury: helpers.treasury.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            tokenProgram: splToken.TOKEN_PROGRAM_ID,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          })
+          .signers([newLender])
+          .rpc();
+
+        const lenderPostRefinanceBalance = await connection.getBalance(
+          lender.keypair.publicKey
+        );
+        const loan = await borrower.program.account.loan.fetch(
+          borrower.loanAccount
+        );
+        const borrowerTokenAccount = await splToken.getAccount(
+          connection,
+          borrower.depositTokenAccount
+        );
+
+        assert(lenderPostRefinanceBalance > lenderPreRefinanceBalance);
+        assert.equal(loan.lender.toBase58(), newLender.publicKey.toBase58());
+        assert.equal(loan.amount.toNumber(), options.amount * 2);
+        assert.deepEqual(loan.state, { active: {} });
+        assert.ok(borrowerTokenAccount.isFrozen);
+      });
+
+      it("Does not allow lenders to refinance a loan to themselves", async () => {
+        const borrower = await helpers.initLoan(connection, options);
+        const lender = await helpers.giveLoan(connection, borrower);
+
+        try {
+          await borrower.program.methods
+            .refinanceLoan({
+              amount: new anchor.BN(options.amount),
+              basisPoints: new anchor.BN(options.basisPoints),
+              duration: new anchor.BN(options.duration),
+              interestMode: { fullTerm: {} },
+              minimumFee: new anchor.BN(0),
+              currencyMint: null,
+              liquidationThreshold: null,
+              priceFeed: null,
+              gracePeriod: new anchor.BN(0),
+              lateFeeBasisPoints: 0,
+              defaultMode: { repossess: {} },
+              noticePeriod: null,
+              listingExpiry: null,
+              counterparty: null,
+            })
+            .accounts({
+              loan: borrower.loanAccount,
+              borrower: borrower.keypair.publicKey,
+              lender: lender.keypair.publicKey,
+              newLender: lender.keypair.publicKey,
+              mint: borrower.mint,
+              protocolConfig: await helpers.findProtocolConfigAddress(),
+              treasury: helpers.treasury.publicKey,
+              systemProgram: anchor.web3.SystemProgram.programId,
+              tokenProgram: splToken.TOKEN_PROGRAM_ID,
+              clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+            })
+            .signers([lender.keypair])
+            .rpc();
+          assert.ok(false);
+        } catch (error) {
+          assert.ok(
+            error.toString().includes("A raw constraint was violated")
+          );
+        }
+      });
+
+      it("Allows loans to be extended with the lender's approval", async () => {
+        const borrower = await helpers.initLoan(connection, {
+          ...options,
+          interestMode: "proRata",
+        });
+        const lender = await helpers.giveLoan(connection, borrower);
+        const loanExtension = await helpers.findLoanExtensionAddress(
+          borrower.mint,
+          borrower.keypair.publicKey
+        );
+        const duration = new anchor.BN(options.duration * 2);
+
+        await borrower.program.methods
+          .proposeLoanExtension({
+            duration,
+            basisPoints: new anchor.BN(options.basisPoints * 2),
+            capitalise: true,
+          })
+          .accounts({
+            loanExtension,
+            loan: borrower.loanAccount,
+            borrower: borrower.keypair.publicKey,
+            lender: lender.keypair.publicKey,
+            mint: borrower.mint,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .rpc();
+
+        await lender.program.methods
+          .acceptLoanExtension()
+          .accounts({
+            loanExtension,
+            loan: borrower.loanAccount,
+            borrower: borrower.keypair.publicKey,
+            lender: lender.keypair.publicKey,
+            mint: borrower.mint,
+            protocolConfig: await helpers.findProtocolConfigAddress(),
+            treasury: helpers.treasury.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          })
+          .rpc();
+
+        const loan = await borrower.program.account.loan.fetch(
+          borrower.loanAccount
+        );
+        const loanExtensionAccountInfo = await connection.getAccountInfo(
+          loanExtension
+        );
+
+        assert.equal(loan.duration.toNumber(), duration.toNumber());
+        assert.equal(loan.basisPoints, options.basisPoints * 2);
+        assert(loan.amount.toNumber() >= options.amount);
+        assert.deepEqual(loan.state, { active: {} });
+        assert.equal(loanExtensionAccountInfo, null);
+      });
+
+      it("Settles escrowed interest when a loan extension is accepted", async () => {
+        const borrower = await helpers.initLoan(connection, options);
+        const lender = await helpers.giveLoan(connection, borrower);
+        const loanExtension = await helpers.findLoanExtensionAddress(
+          borrower.mint,
+          borrower.keypair.publicKey
+        );
+
+        await borrower.program.methods
+          .proposeLoanExtension({
+            duration: new anchor.BN(options.duration * 2),
+            basisPoints: null,
+            capitalise: false,
+          })
+          .accounts({
+            loanExtension,
+            loan: borrower.loanAccount,
+            borrower: borrower.keypair.publicKey,
+            lender: lender.keypair.publicKey,
+            mint: borrower.mint,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .rpc();
+
+        const extension = await borrower.program.account.loanExtension.fetch(
+          loanExtension
+        );
+        // Full term interest is accrued up front and rounded up
+        const fullTermFee = new anchor.BN(options.amount)
+          .mul(new anchor.BN(options.basisPoints))
+          .mul(new anchor.BN(options.duration))
+          .add(new anchor.BN(10_000 * 31_536_000 - 1))
+          .div(new anchor.BN(10_000 * 31_536_000))
+          .toNumber();
+
+        assert.equal(extension.escrowBalance.toNumber(), fullTermFee);
+        assert.equal(
+          extension.lender.toBase58(),
+          lender.keypair.publicKey.toBase58()
+        );
+
+        const lenderPreAcceptBalance = await connection.getBalance(
+          lender.keypair.publicKey
+        );
+
+        await lender.program.methods
+          .acceptLoanExtension()
+          .accounts({
+            loanExtension,
+            loan: borrower.loanAccount,
+            borrower: borrower.keypair.publicKey,
+            lender: lender.keypair.publicKey,
+            mint: borrower.mint,
+            protocolConfig: await helpers.findProtocolConfigAddress(),
+            treasury: helpers.treasury.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          })
+          .rpc();
+
+        const lenderPostAcceptBalance = await connection.getBalance(
+          lender.keypair.publicKey
+        );
+        const loan = await borrower.program.account.loan.fetch(
+          borrower.loanAccount
+        );
+
+        assert.equal(
+          lenderPostAcceptBalance - lenderPreAcceptBalance,
+          fullTermFee - 5_000
+        );
+        assert.equal(loan.interestPaid.toNumber(), fullTermFee);
+        assert.equal(loan.amount.toNumber(), options.amount);
+      });
+
+      it("Refunds escrowed interest when a loan extension is cancelled", async () => {
+        const borrower = await helpers.initLoan(connection, options);
+        const lender = await helpers.giveLoan(connection, borrower);
+        const loanExtension = await helpers.findLoanExtensionAddress(
+          borrower.mint,
+          borrower.keypair.publicKey
+        );
+        const borrowerPreProposalBalance = await connection.getBalance(
+          borrower.keypair.publicKey
+        );
+
+        await borrower.program.methods
+          .proposeLoanExtension({
+            duration: new anchor.BN(options.duration * 2),
+            basisPoints: null,
+            capitalise: false,
+          })
+          .accounts({
+            loanExtension,
+            loan: borrower.loanAccount,
+            borrower: borrower.keypair.publicKey,
+            lender: lender.keypair.publicKey,
+            mint: borrower.mint,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .rpc();
+
+        await borrower.program.methods
+          .cancelLoanExtension()
+          .accounts({
+            loanExtension,
+            borrower: borrower.keypair.publicKey,
+            mint: borrower.mint,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+
+        const borrowerPostCancelBalance = await connection.getBalance(
+          borrower.keypair.publicKey
+        );
+
+        // Only transaction fees are lost
+        assert.equal(
+          borrowerPreProposalBalance - borrowerPostCancelBalance,
+          10_000
+        );
+      });
+
+      it("Allows listed loans to be updated", async () => {
+        const borrower = await helpers.initLoan(connection, options);
+        const amount = new anchor.BN(options.amount * 2);
+        const duration = new anchor.BN(options.duration * 2);
+
+        await borrower.program.methods
+          .updateLoan({
+            amount,
+            basisPoints: new anchor.BN(options.basisPoints),
+            duration,
+            interestMode: { fullTerm: {} },
+            minimumFee: new anchor.BN(0),
+            currencyMint: null,
+            liquidationThreshold: null,
+            priceFeed: null,
+            gracePeriod: new anchor.BN(0),
+            lateFeeBasisPoints: 0,
+            defaultMode: { repossess: {} },
+            noticePeriod: null,
+            listingExpiry: null,
+            counterparty: null,
+          })
+          .accounts({
+            borrower: borrower.keypair.publicKey,
+            loan: borrower.loanAccount,
+            mint: borrower.mint,
+          })
+          .rpc();
+
+        const loan = await borrower.program.account.loan.fetch(
+          borrower.loanAccount
+        );
+        const tokenAccount = await splToken.getAccount(
+          connection,
+          borrower.depositTokenAccount
+        );
+
+        assert.equal(loan.amount.toNumber(), amount.toNumber());
+        assert.equal(loan.duration.toNumber(), duration.toNumber());
+        assert.deepEqual(loan.state, { listed: {} });
+        assert.ok(tokenAccount.isFrozen);
+        assert.equal(
+          tokenAccount.delegate.toBase58(),
+          borrower.tokenManager.toBase58()
+        );
+      });
+
+      it("Prevents reinitialization", async () => {
+        const amount = anchor.web3.LAMPORTS_PER_SOL;
+        const basisPoints = 500;
+        const duration = 60;
+
+        const borrower = await helpers.initLoan(connection, {
+          amount,
+          basisPoints,
+          duration,
+        });
+        await helpers.giveLoan(connection, borrower);
+
+        try {
+          await borrower.program.methods
+            .initLoan({
+              amount: new anchor.BN(amount),
+              basisPoints: new anchor.BN(basisPoints),
+              duration: new anchor.BN(1),
+              interestMode: { fullTerm: {} },
+              minimumFee: new anchor.BN(0),
+              currencyMint: null,
+              liquidationThreshold: null,
+              priceFeed: null,
+              gracePeriod: new anchor.BN(0),
+              lateFeeBasisPoints: 0,
+              defaultMode: { repossess: {} },
+              noticePeriod: null,
+              listingExpiry: null,
+              counterparty: null,
+            })
+            .accounts({
+              loan: borrower.loanAccount,
+              tokenManager: borrower.tokenManager,
+              depositTokenAccount: borrower.depositTokenAccount,
+              mint: borrower.mint,
+              borrower: borrower.keypair.publicKey,
+              edition: borrower.edition,
+              metadataProgram: METADATA_PROGRAM_ID,
+              tokenProgram: splToken.TOKEN_PROGRAM_ID,
+              rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+              systemProgram: anchor.web3.SystemProgram.programId,
+            })
+            .rpc();
+          assert.fail();
+        } catch (error) {
+          assert.ok(error.toString().includes("custom program error: 0x0"));
+        }
+      });
+    });
+  });
+
+  describe("Token denominated loans", () => {
+    it("Moves principal and repayment as SPL tokens", async () => {
+      const options = {
+        amount: 100_000_000,
+        basisPoints: 500,
+        duration: 30 * 24 * 60 * 60, // 30 days
+      };
+
+      const lenderKeypair = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, lenderKeypair.publicKey);
+      const currencyMint = await splToken.createMint(
+        connection,
+        lenderKeypair,
+        lenderKeypair.publicKey,
+        null,
+        6
+      );
+      const lenderCurrencyAccount =
+        await splToken.getOrCreateAssociatedTokenAccount(
+          connection,
+          lenderKeypair,
+          currencyMint,
+          lenderKeypair.publicKey
+        );
+      await splToken.mintTo(
+        connection,
+        lenderKeypair,
+        currencyMint,
+        lenderCurrencyAccount.address,
+        lenderKeypair,
+        options.amount * 2
+      );
+
+      const borrower = await helpers.initLoan(connection, {
+        ...options,
+        currencyMint,
+      });
+      const borrowerCurrencyAccount =
+        await splToken.getOrCreateAssociatedTokenAccount(
+          connection,
+          borrower.keypair,
+          currencyMint,
+          borrower.keypair.publicKey
+        );
+      const currencyAccounts = [
+        borrowerCurrencyAccount.address,
+        lenderCurrencyAccount.address,
+      ].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+
+      const lender = await helpers.giveLoan(
+        connection,
+        borrower,
+        currencyAccounts,
+        lenderKeypair
+      );
+
+      let borrowerCurrencyBalance = await splToken.getAccount(
+        connection,
+        borrowerCurrencyAccount.address
+      );
+      assert.equal(borrowerCurrencyBalance.amount, BigInt(options.amount));
+
+      // Borrower needs to cover the interest
+      await splToken.mintTo(
+        connection,
+        lenderKeypair,
+        currencyMint,
+        borrowerCurrencyAccount.address,
+        lenderKeypair,
+        options.amount
+      );
+      const treasuryCurrencyAccount =
+        await splToken.getOrCreateAssociatedTokenAccount(
+          connection,
+          lenderKeypair,
+          currencyMint,
+          helpers.treasury.publicKey
+        );
+
+      await borrower.program.methods
+        .repayLoan()
+        .accounts({
+          protocolConfig: await helpers.findProtocolConfigAddress(),
+          treasury: helpers.treasury.publicKey,
+          loan: borrower.loanAccount,
+          tokenManager: borrower.tokenManager,
+          borrower: borrower.keypair.publicKey,
+          depositTokenAccount: borrower.depositTokenAccount,
+          lender: lender.keypair.publicKey,
+          mint: borrower.mint,
+          edition: borrower.edition,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .remainingAccounts([
+          ...currencyAccounts,
+          {
+            pubkey: treasuryCurrencyAccount.address,
+            isSigner: false,
+            isWritable: true,
+          },
+        ])
+        .rpc();
+
+      const lenderCurrencyBalance = await splToken.getAccount(
+        connection,
+        lenderCurrencyAccount.address
+      );
+      borrowerCurrencyBalance = await splToken.getAccount(
+        connection,
+        borrowerCurrencyAccount.address
+      );
+      const borrowerTokenAccount = await splToken.getAccount(
+        connection,
+        borrower.depositTokenAccount
+      );
+
+      assert(lenderCurrencyBalance.amount > BigInt(options.amount * 2));
+      assert(borrowerCurrencyBalance.amount < BigInt(options.amount));
+      assert.equal(borrowerTokenAccount.delegate, null);
+    });
+  });
+
+  describe("Loan offers", () => {
+    let options;
+
+    it("Allows a loan offer to be accepted by the NFT holder", async () => {
+      options = {
+        amount: anchor.web3.LAMPORTS_PER_SOL / 10,
+        basisPoints: 500,
+        duration: 30 * 24 * 60 * 60, // 30 days
+        expiry: Date.now() / 1000 + 60 * 60,
+      };
+
+      const keypair = anchor.web3.Keypair.generate();
+      const provider = helpers.getProvider(connection, keypair);
+      const program = helpers.getProgram(provider);
+      await helpers.requestAirdrop(connection, keypair.publicKey);
+      const nft = await helpers.mintNFT(connection, keypair);
+
+      const lender = await helpers.initLoanOffer(
+        connection,
+        nft.mint.address,
+        options
+      );
+
+      const largestAccounts = await connection.getTokenLargestAccounts(
+        nft.mint.address
+      );
+      const depositTokenAccount = largestAccounts.value[0].address;
+      const loanAccount = await helpers.findLoanAddress(
+        nft.mint.address,
+        keypair.publicKey
+      );
+      const tokenManager = await helpers.findTokenManagerAddress(
+        nft.mint.address,
+        keypair.publicKey
+      );
+      const borrowerPreLoanBalance = await connection.getBalance(
+        keypair.publicKey
+      );
+
+      try {
+        await program.methods
+          .acceptLoanOffer(
+            new anchor.BN(options.amount),
+            options.basisPoints,
+            new anchor.BN(1)
+          )
+          .accounts({
+            tokenManager,
+            depositTokenAccount,
+            loanOffer: lender.loanOffer,
+            loan: loanAccount,
+            borrower: keypair.publicKey,
+            lender: lender.keypair.publicKey,
+            mint: nft.mint.address,
+            edition: nft.edition.address,
+            metadataProgram: METADATA_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            tokenProgram: splToken.TOKEN_PROGRAM_ID,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .rpc();
+        assert.ok(false);
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "TermsMismatch");
+      }
+
+      await program.methods
+        .acceptLoanOffer(
+          new anchor.BN(options.amount),
+          options.basisPoints,
+          new anchor.BN(options.duration)
+        )
+        .accounts({
+          tokenManager,
+          depositTokenAccount,
+          loanOffer: lender.loanOffer,
+          loan: loanAccount,
+          borrower: keypair.publicKey,
+          lender: lender.keypair.publicKey,
+          mint: nft.mint.address,
+          edition: nft.edition.address,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+
+      const loan = await program.account.loan.fetch(loanAccount);
+      const borrowerPostLoanBalance = await connection.getBalance(
+        keypair.publicKey
+      );
+      const borrowerTokenAccount = await splToken.getAccount(
+        connection,
+        depositTokenAccount
+      );
+      const loanOfferAccountInfo = await connection.getAccountInfo(
+        lender.loanOffer
+      );
+
+      assert.deepEqual(loan.state, { active: {} });
+      assert.equal(loan.lender.toBase58(), lender.keypair.publicKey.toBase58());
+      assert.equal(loan.amount.toNumber(), options.amount);
+      assert(borrowerPostLoanBalance > borrowerPreLoanBalance);
+      assert.ok(borrowerTokenAccount.isFrozen);
+      assert.equal(loanOfferAccountInfo, null);
+    });
+
+    it("Rejects a loan offer without a duration", async () => {
+      const keypair = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, keypair.publicKey);
+      const nft = await helpers.mintNFT(connection, keypair);
+
+      try {
+        await helpers.initLoanOffer(connection, nft.mint.address, {
+          ...options,
+          duration: 0,
+        });
+        assert.ok(false);
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "InvalidDuration");
+      }
+    });
+
+    it("Allows a loan offer to be cancelled by the lender", async () => {
+      const keypair = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, keypair.publicKey);
+      const nft = await helpers.mintNFT(connection, keypair);
+
+      const lender = await helpers.initLoanOffer(
+        connection,
+        nft.mint.address,
+        options
+      );
+      const lenderPreCancelBalance = await connection.getBalance(
+        lender.keypair.publicKey
+      );
+
+      await lender.program.methods
+        .cancelLoanOffer()
+        .accounts({
+          loanOffer: lender.loanOffer,
+          lender: lender.keypair.publicKey,
+          mint: nft.mint.address,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const lenderPostCancelBalance = await connection.getBalance(
+        lender.keypair.publicKey
+      );
+
+      assert(lenderPostCancelBalance > lenderPreCancelBalance + options.amount);
+    });
+
+    it("Only allows verified collection members to accept a collection loan offer", async () => {
+      const lenderKeypair = anchor.web3.Keypair.generate();
+      const lenderProvider = helpers.getProvider(connection, lenderKeypair);
+      const lenderProgram = helpers.getProgram(lenderProvider);
+      await helpers.requestAirdrop(connection, lenderKeypair.publicKey);
+      const collectionNft = await helpers.mintNFT(connection, lenderKeypair);
+
+      const collectionLoanOffer =
+        await helpers.findCollectionLoanOfferAddress(
+          collectionNft.mint.address,
+          lenderKeypair.publicKey
+        );
+
+      await lenderProgram.methods
+        .initCollectionLoanOffer({
+          amount: new anchor.BN(options.amount),
+          basisPoints: new anchor.BN(options.basisPoints),
+          duration: new anchor.BN(options.duration),
+          expiry: new anchor.BN(options.expiry),
+          count: 2,
+        })
+        .accounts({
+          collectionLoanOffer,
+          collection: collectionNft.mint.address,
+          lender: lenderKeypair.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+
+      let offer = await lenderProgram.account.collectionLoanOffer.fetch(
+        collectionLoanOffer
+      );
+      assert.equal(offer.count, 2);
+
+      const keypair = anchor.web3.Keypair.generate();
+      const provider = helpers.getProvider(connection, keypair);
+      const program = helpers.getProgram(provider);
+      await helpers.requestAirdrop(connection, keypair.publicKey);
+      const nft = await helpers.mintNFT(connection, keypair);
+
+      const largestAccounts = await connection.getTokenLargestAccounts(
+        nft.mint.address
+      );
+
+      try {
+        await program.methods
+          .acceptCollectionLoanOffer(
+            new anchor.BN(options.amount),
+            options.basisPoints,
+            new anchor.BN(options.duration)
+          )
+          .accounts({
+            collectionLoanOffer,
+            borrower: keypair.publicKey,
+            lender: lenderKeypair.publicKey,
+            depositTokenAccount: largestAccounts.value[0].address,
+            loan: await helpers.findLoanAddress(
+              nft.mint.address,
+              keypair.publicKey
+            ),
+            tokenManager: await helpers.findTokenManagerAddress(
+              nft.mint.address,
+              keypair.publicKey
+            ),
+            mint: nft.mint.address,
+            edition: nft.edition.address,
+            metadata: nft.metadataAddress,
+            metadataProgram: METADATA_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            tokenProgram: splToken.TOKEN_PROGRAM_ID,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .rpc();
+        assert.ok(false);
+      } catch (error) {
+        assert.ok(error.toString().includes("Invalid collection"));
+      }
+
+      offer = await lenderProgram.account.collectionLoanOffer.fetch(
+        collectionLoanOffer
+      );
+      assert.equal(offer.count, 2);
+    });
+
+    it("Rejects a collection loan offer without a duration", async () => {
+      const lenderKeypair = anchor.web3.Keypair.generate();
+      const lenderProvider = helpers.getProvider(connection, lenderKeypair);
+      const lenderProgram = helpers.getProgram(lenderProvider);
+      await helpers.requestAirdrop(connection, lenderKeypair.publicKey);
+      const collectionNft = await helpers.mintNFT(connection, lenderKeypair);
+
+      try {
+        await lenderProgram.methods
+          .initCollectionLoanOffer({
+            amount: new anchor.BN(options.amount),
+            basisPoints: new anchor.BN(options.basisPoints),
+            duration: new anchor.BN(0),
+            expiry: new anchor.BN(options.expiry),
+            count: 2,
+          })
+          .accounts({
+            collectionLoanOffer: await helpers.findCollectionLoanOfferAddress(
+              collectionNft.mint.address,
+              lenderKeypair.publicKey
+            ),
+            collection: collectionNft.mint.address,
+            lender: lenderKeypair.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .rpc();
+        assert.ok(false);
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "InvalidDuration");
+      }
+    });
+  });
+
+  describe("Lender positions", () => {
+    it("Allows the lender to sell their position", async () => {
+      const options = {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 30 * 24 * 60 * 60, // 30 days
+      };
+      const borrower = await helpers.initLoan(connection, options);
+      const lender = await helpers.giveLoan(connection, borrower);
+
+      const newLenderKeypair = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, newLenderKeypair.publicKey);
+      const price = anchor.web3.LAMPORTS_PER_SOL / 2;
+      const lenderPreSaleBalance = await connection.getBalance(
+        lender.keypair.publicKey
+      );
+
+      await lender.program.methods
+        .transferLenderPosition(new anchor.BN(price))
+        .accounts({
+          borrower: borrower.keypair.publicKey,
+          lender: lender.keypair.publicKey,
+          newLender: newLenderKeypair.publicKey,
+          loan: borrower.loanAccount,
+          mint: borrower.mint,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        })
+        .signers([newLenderKeypair])
+        .rpc();
+
+      const lenderPostSaleBalance = await connection.getBalance(
+        lender.keypair.publicKey
+      );
+      const loan = await borrower.program.account.loan.fetch(
+        borrower.loanAccount
+      );
+
+      assert.equal(
+        loan.lender.toBase58(),
+        newLenderKeypair.publicKey.toBase58()
+      );
+      assert(lenderPostSaleBalance - lenderPreSaleBalance >= price - 10_000);
+
+      const newLenderPreRepaymentBalance = await connection.getBalance(
+        newLenderKeypair.publicKey
+      );
+
+      await borrower.program.methods
+        .repayLoan()
+        .accounts({
+          protocolConfig: await helpers.findProtocolConfigAddress(),
+          treasury: helpers.treasury.publicKey,
+          loan: borrower.loanAccount,
+          tokenManager: borrower.tokenManager,
+          borrower: borrower.keypair.publicKey,
+          depositTokenAccount: borrower.depositTokenAccount,
+          lender: newLenderKeypair.publicKey,
+          mint: borrower.mint,
+          edition: borrower.edition,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+
+      const newLenderPostRepaymentBalance = await connection.getBalance(
+        newLenderKeypair.publicKey
+      );
+
+      assert(
+        newLenderPostRepaymentBalance - newLenderPreRepaymentBalance >=
+          options.amount
+      );
+    });
+
+    it("Mints a 1/1 loan note to the lender", async () => {
+      const borrower = await helpers.initLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 30 * 24 * 60 * 60, // 30 days
+      });
+      const lender = await helpers.giveLoan(connection, borrower);
+      const { startDate } = await borrower.program.account.loan.fetch(
+        borrower.loanAccount
+      );
+      const noteMint = await helpers.findLoanNoteAddress(
+        borrower.loanAccount,
+        startDate
+      );
+      const noteTokenAccount = await splToken.getAssociatedTokenAddress(
+        noteMint,
+        lender.keypair.publicKey
+      );
+
+      await lender.program.methods
+        .mintLoanNote()
+        .accounts({
+          borrower: borrower.keypair.publicKey,
+          lender: lender.keypair.publicKey,
+          loan: borrower.loanAccount,
+          noteMint,
+          noteTokenAccount,
+          mint: borrower.mint,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: splToken.ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+
+      const loan = await borrower.program.account.loan.fetch(
+        borrower.loanAccount
+      );
+      const note = await splToken.getMint(connection, noteMint);
+      const noteAccount = await splToken.getAccount(
+        connection,
+        noteTokenAccount
+      );
+
+      assert.equal(loan.noteMint.toBase58(), noteMint.toBase58());
+      assert.equal(note.supply, BigInt(1));
+      assert.equal(note.mintAuthority, null);
+      assert.equal(noteAccount.amount, BigInt(1));
+    });
+
+    it("Invalidates the loan note when the loan is refinanced", async () => {
+      const options = {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 30 * 24 * 60 * 60, // 30 days
+      };
+      const borrower = await helpers.initLoan(connection, options);
+      const lender = await helpers.giveLoan(connection, borrower);
+      const { startDate } = await borrower.program.account.loan.fetch(
+        borrower.loanAccount
+      );
+      const noteMint = await helpers.findLoanNoteAddress(
+        borrower.loanAccount,
+        startDate
+      );
+      const noteTokenAccount = await splToken.getAssociatedTokenAddress(
+        noteMint,
+        lender.keypair.publicKey
+      );
+
+      await lender.program.methods
+        .mintLoanNote()
+        .accounts({
+          borrower: borrower.keypair.publicKey,
+          lender: lender.keypair.publicKey,
+          loan: borrower.loanAccount,
+          noteMint,
+          noteTokenAccount,
+          mint: borrower.mint,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: splToken.ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+
+      const newLender = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, newLender.publicKey);
+      const noteHolderPreRefinanceBalance = await connection.getBalance(
+        lender.keypair.publicKey
+      );
+
+      await borrower.program.methods
+        .refinanceLoan({
+          amount: new anchor.BN(options.amount),
+          basisPoints: new anchor.BN(options.basisPoints),
+          duration: new anchor.BN(options.duration),
+          interestMode: { fullTerm: {} },
+          minimumFee: new anchor.BN(0),
+          currencyMint: null,
+          liquidationThreshold: null,
+          priceFeed: null,
+          gracePeriod: new anchor.BN(0),
+          lateFeeBasisPoints: 0,
+          defaultMode: { repossess: {} },
+          noticePeriod: null,
+          listingExpiry: null,
+          counterparty: null,
+        })
+        .accounts({
+          loan: borrower.loanAccount,
+          borrower: borrower.keypair.publicKey,
+          lender: lender.keypair.publicKey,
+          newLender: newLender.publicKey,
+          mint: borrower.mint,
+          protocolConfig: await helpers.findProtocolConfigAddress(),
+          treasury: helpers.treasury.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .remainingAccounts([
+          {
+            pubkey: noteTokenAccount,
+            isSigner: false,
+            isWritable: false,
+          },
+        ])
+        .signers([newLender])
+        .rpc();
+
+      const noteHolderPostRefinanceBalance = await connection.getBalance(
+        lender.keypair.publicKey
+      );
+      const loan = await borrower.program.account.loan.fetch(
+        borrower.loanAccount
+      );
+
+      assert.equal(loan.noteMint, null);
+      assert.equal(loan.lender.toBase58(), newLender.publicKey.toBase58());
+      assert(
+        noteHolderPostRefinanceBalance - noteHolderPreRefinanceBalance >=
+          options.amount
+      );
+
+      // The old note holder can no longer act as the lender
+      try {
+        await lender.program.methods
+          .recallLoan()
+          .accounts({
+            loan: borrower.loanAccount,
+            borrower: borrower.keypair.publicKey,
+            lender: lender.keypair.publicKey,
+            mint: borrower.mint,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          })
+          .remainingAccounts([
+            {
+              pubkey: noteTokenAccount,
+              isSigner: false,
+              isWritable: false,
+            },
+          ])
+          .rpc();
+        assert.ok(false);
+      } catch (error) {
+        assert.ok(error.toString().includes("A raw constraint was violated"));
+      }
+    });
+  });
+
+  describe("Protocol fees", () => {
+    const fees = {
+      treasury: helpers.treasury.publicKey,
+      loanFeeBasisPoints: 1_000,
+      optionPremiumFeeBasisPoints: 0,
+      optionExerciseFeeBasisPoints: 0,
+      hireFeeBasisPoints: 0,
+      oracleProgram: helpers.oracleProgram,
+    };
+
+    it("Only allows the admin to update the config", async () => {
+      const keypair = anchor.web3.Keypair.generate();
+      const program = helpers.getProgram(
+        helpers.getProvider(connection, keypair)
+      );
+      await helpers.requestAirdrop(connection, keypair.publicKey);
+
+      try {
+        await program.methods
+          .updateProtocolConfig(fees)
+          .accounts({
+            admin: keypair.publicKey,
+            protocolConfig: admin.protocolConfig,
+          })
+          .rpc();
+        assert.ok(false);
+      } catch (error) {
+        assert.ok(
+          error.toString().includes("A has one constraint was violated")
+        );
+      }
+    });
+
+    it("Pays a share of loan interest to the treasury", async () => {
+      const options = {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 5_000,
+        duration: 30 * 24 * 60 * 60, // 30 days
+      };
+
+      await admin.program.methods
+        .updateProtocolConfig(fees)
+        .accounts({
+          admin: admin.keypair.publicKey,
+          protocolConfig: admin.protocolConfig,
+        })
+        .rpc();
+
+      const borrower = await helpers.initLoan(connection, options);
+      const lender = await helpers.giveLoan(connection, borrower);
+      const treasuryPreRepaymentBalance = await connection.getBalance(
+        helpers.treasury.publicKey
+      );
+
+      await borrower.program.methods
+        .repayLoan()
+        .accounts({
+          loan: borrower.loanAccount,
+          tokenManager: borrower.tokenManager,
+          borrower: borrower.keypair.publicKey,
+          depositTokenAccount: borrower.depositTokenAccount,
+          lender: lender.keypair.publicKey,
+          mint: borrower.mint,
+          edition: borrower.edition,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          protocolConfig: admin.protocolConfig,
+          treasury: helpers.treasury.publicKey,
+        })
+        .rpc();
+
+      const treasuryPostRepaymentBalance = await connection.getBalance(
+        helpers.treasury.publicKey
+      );
+
+      await admin.program.methods
+        .updateProtocolConfig({ ...fees, loanFeeBasisPoints: 0 })
+        .accounts({
+          admin: admin.keypair.publicKey,
+          protocolConfig: admin.protocolConfig,
+        })
+        .rpc();
+
+      const interest = Math.round(
+        (options.amount * options.basisPoints * options.duration) /
+          (10_000 * 31_536_000)
+      );
+
+      const protocolFee = interest / 10;
+
+      assert.ok(
+        Math.abs(
+          treasuryPostRepaymentBalance -
+            treasuryPreRepaymentBalance -
+            protocolFee
+        ) <=
+          protocolFee / 1_000
+      );
+    });
+
+    it("Allows the admin to be rotated", async () => {
+      const newAdmin = anchor.web3.Keypair.generate();
+
+      await admin.program.methods
+        .setProtocolAdmin()
+        .accounts({
+          admin: admin.keypair.publicKey,
+          newAdmin: newAdmin.publicKey,
+          protocolConfig: admin.protocolConfig,
+        })
+        .signers([newAdmin])
+        .rpc();
+
+      const protocolConfig = await admin.program.account.protocolConfig.fetch(
+        admin.protocolConfig
+      );
+
+      assert.equal(
+        protocolConfig.admin.toBase58(),
+        newAdmin.publicKey.toBase58()
+      );
+    });
+  });
+
+  describe("Liquidations", () => {
+    it("Requires a price feed when a liquidation threshold is set", async () => {
+      try {
+        await helpers.initLoan(connection, {
+          amount: anchor.web3.LAMPORTS_PER_SOL,
+          basisPoints: 500,
+          duration: 30 * 24 * 60 * 60, // 30 days
+          liquidationThreshold: 8_000,
+        });
+        assert.ok(false);
+      } catch (error) {
+        assert.ok(
+          error.toString().includes("Invalid liquidation threshold")
+        );
+      }
+    });
+
+    it("Rejects a liquidation threshold above 10,000 basis points", async () => {
+      try {
+        await helpers.initLoan(connection, {
+          amount: anchor.web3.LAMPORTS_PER_SOL,
+          basisPoints: 500,
+          duration: 30 * 24 * 60 * 60, // 30 days
+          liquidationThreshold: 10_001,
+          priceFeed: anchor.web3.Keypair.generate().publicKey,
+        });
+        assert.ok(false);
+      } catch (error) {
+        assert.ok(
+          error.toString().includes("Invalid liquidation threshold")
+        );
+      }
+    });
+
+    it("Will not liquidate a loan without a price feed", async () => {
+      const borrower = await helpers.initLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 30 * 24 * 60 * 60, // 30 days
+      });
+      const lender = await helpers.giveLoan(connection, borrower);
+
+      const tokenAccount = await splToken.getOrCreateAssociatedTokenAccount(
+        connection,
+        lender.keypair,
+        borrower.mint,
+        lender.keypair.publicKey
+      );
+
+      try {
+        await lender.program.methods
+          .liquidateLoan()
+          .accounts({
+            borrower: borrower.keypair.publicKey,
+            depositTokenAccount: borrower.depositTokenAccount,
+            lender: lender.keypair.publicKey,
+            lenderTokenAccount: tokenAccount.address,
+            loan: borrower.loanAccount,
+            tokenManager: borrower.tokenManager,
+            priceFeed: anchor.web3.Keypair.generate().publicKey,
+            protocolConfig: await helpers.findProtocolConfigAddress(),
+            mint: borrower.mint,
+            edition: borrower.edition,
+            metadataProgram: METADATA_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            tokenProgram: splToken.TOKEN_PROGRAM_ID,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .rpc();
+        assert.ok(false);
+      } catch (error) {
+        assert.ok(error.toString().includes("A raw constraint was violated"));
+      }
+
+      const loan = await borrower.program.account.loan.fetch(
+        borrower.loanAccount
+      );
+      assert.deepEqual(loan.state, { active: {} });
+    });
+
+    it("Rejects a price feed not owned by the oracle program", async () => {
+      // A wallet is owned by the system program rather than the oracle
+      const priceFeed = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, priceFeed.publicKey);
+
+      const borrower = await helpers.initLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 30 * 24 * 60 * 60, // 30 days
+        liquidationThreshold: 8_000,
+        priceFeed: priceFeed.publicKey,
+      });
+      const lender = await helpers.giveLoan(connection, borrower);
+
+      const tokenAccount = await splToken.getOrCreateAssociatedTokenAccount(
+        connection,
+        lender.keypair,
+        borrower.mint,
+        lender.keypair.publicKey
+      );
+
+      try {
+        await lender.program.methods
+          .liquidateLoan()
+          .accounts({
+            borrower: borrower.keypair.publicKey,
+            depositTokenAccount: borrower.depositTokenAccount,
+            lender: lender.keypair.publicKey,
+            lenderTokenAccount: tokenAccount.address,
+            loan: borrower.loanAccount,
+            tokenManager: borrower.tokenManager,
+            priceFeed: priceFeed.publicKey,
+            protocolConfig: await helpers.findProtocolConfigAddress(),
+            mint: borrower.mint,
+            edition: borrower.edition,
+            metadataProgram: METADATA_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            tokenProgram: splToken.TOKEN_PROGRAM_ID,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .rpc();
+        assert.ok(false);
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "InvalidPriceFeed");
+      }
+    });
+  });
+
+  describe("Grace periods", () => {
+    it("Charges a late fee and blocks repossession during the grace period", async () => {
+      const options = {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 1,
+        gracePeriod: 24 * 60 * 60, // 1 day
+        lateFeeBasisPoints: 100,
+      };
+      const borrower = await helpers.initLoan(connection, options);
+      const lender = await helpers.giveLoan(connection, borrower);
+      await helpers.wait(2);
+
+      const tokenAccount = await splToken.getOrCreateAssociatedTokenAccount(
+        connection,
+        lender.keypair,
+        borrower.mint,
+        lender.keypair.publicKey
+      );
+
+      try {
+        await lender.program.methods
+          .repossess()
+          .accounts({
+            borrower: borrower.keypair.publicKey,
+            depositTokenAccount: borrower.depositTokenAccount,
+            lender: lender.keypair.publicKey,
+            lenderTokenAccount: tokenAccount.address,
+            loan: borrower.loanAccount,
+            tokenManager: borrower.tokenManager,
+            mint: borrower.mint,
+            edition: borrower.edition,
+            metadataProgram: METADATA_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            tokenProgram: splToken.TOKEN_PROGRAM_ID,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .rpc();
+        assert.ok(false);
+      } catch (error) {
+        assert.ok(error.toString().includes("This loan is not overdue"));
+      }
+
+      const lenderPreRepaymentBalance = await connection.getBalance(
+        lender.keypair.publicKey
+      );
+
+      await borrower.program.methods
+        .repayLoan()
+        .accounts({
+          protocolConfig: await helpers.findProtocolConfigAddress(),
+          treasury: helpers.treasury.publicKey,
+          loan: borrower.loanAccount,
+          tokenManager: borrower.tokenManager,
+          borrower: borrower.keypair.publicKey,
+          depositTokenAccount: borrower.depositTokenAccount,
+          lender: lender.keypair.publicKey,
+          mint: borrower.mint,
+          edition: borrower.edition,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+
+      const lenderPostRepaymentBalance = await connection.getBalance(
+        lender.keypair.publicKey
+      );
+      const lateFee = options.amount / 100;
+
+      assert.ok(
+        lenderPostRepaymentBalance - lenderPreRepaymentBalance >=
+          options.amount + lateFee
+      );
+    });
+  });
+
+  describe("Callable loans", () => {
+    it("Allows the lender to repossess once the notice period ends", async () => {
+      const borrower = await helpers.initLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 0,
+        interestMode: "proRata",
+        noticePeriod: 1,
+      });
+      const lender = await helpers.giveLoan(connection, borrower);
+
+      const tokenAccount = await splToken.getOrCreateAssociatedTokenAccount(
+        connection,
+        lender.keypair,
+        borrower.mint,
+        lender.keypair.publicKey
+      );
+
+      function repossess() {
+        return lender.program.methods
+          .repossess()
+          .accounts({
+            borrower: borrower.keypair.publicKey,
+            depositTokenAccount: borrower.depositTokenAccount,
+            lender: lender.keypair.publicKey,
+            lenderTokenAccount: tokenAccount.address,
+            loan: borrower.loanAccount,
+            tokenManager: borrower.tokenManager,
+            mint: borrower.mint,
+            edition: borrower.edition,
+            metadataProgram: METADATA_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            tokenProgram: splToken.TOKEN_PROGRAM_ID,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .rpc();
+      }
+
+      // Open-ended loans can't be repossessed before they are recalled
+      await helpers.wait(2);
+
+      try {
+        await repossess();
+        assert.ok(false);
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "NotOverdue");
+      }
+
+      await lender.program.methods
+        .recallLoan()
+        .accounts({
+          borrower: borrower.keypair.publicKey,
+          lender: lender.keypair.publicKey,
+          loan: borrower.loanAccount,
+          mint: borrower.mint,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+
+      let loan = await borrower.program.account.loan.fetch(
+        borrower.loanAccount
+      );
+      assert.deepEqual(loan.state, { recalled: {} });
+      assert.ok(loan.recalledAt.toNumber() > 0);
+
+      await helpers.wait(2);
+      await repossess();
+
+      loan = await borrower.program.account.loan.fetch(borrower.loanAccount);
+      const lenderTokenAccount = await splToken.getAccount(
+        connection,
+        tokenAccount.address
+      );
+      assert.deepEqual(loan.state, { defaulted: {} });
+      assert.equal(lenderTokenAccount.amount, BigInt(1));
+    });
+
+    it("Does not allow fixed term loans to be recalled", async () => {
+      const borrower = await helpers.initLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 30 * 24 * 60 * 60, // 30 days
+      });
+      const lender = await helpers.giveLoan(connection, borrower);
+
+      try {
+        await lender.program.methods
+          .recallLoan()
+          .accounts({
+            borrower: borrower.keypair.publicKey,
+            lender: lender.keypair.publicKey,
+            loan: borrower.loanAccount,
+            mint: borrower.mint,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          })
+          .rpc();
+        assert.ok(false);
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "InvalidState");
+      }
+    });
+  });
+
+  describe("Listing expiry", () => {
+    it("Does not allow an expired loan listing to be funded", async () => {
+      const borrower = await helpers.initLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 30 * 24 * 60 * 60, // 30 days
+        listingExpiry: Math.round(Date.now() / 1000) + 1,
+      });
+      await helpers.wait(3);
+
+      try {
+        await helpers.giveLoan(connection, borrower);
+        assert.ok(false);
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "ListingExpired");
+      }
+
+      const keypair = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, keypair.publicKey);
+      const program = helpers.getProgram(
+        helpers.getProvider(connection, keypair)
+      );
+      const beforeBorrowerBalance = await connection.getBalance(
+        borrower.keypair.publicKey
+      );
+
+      await program.methods
+        .closeExpiredListing()
+        .accounts({
+          signer: keypair.publicKey,
+          creator: borrower.keypair.publicKey,
+          listing: borrower.loanAccount,
+          depositTokenAccount: borrower.depositTokenAccount,
+          tokenManager: borrower.tokenManager,
+          mint: borrower.mint,
+          edition: borrower.edition,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+
+      const afterBorrowerBalance = await connection.getBalance(
+        borrower.keypair.publicKey
+      );
+      const loanAccount = await connection.getAccountInfo(borrower.loanAccount);
+      const tokenAccount = await splToken.getAccount(
+        connection,
+        borrower.depositTokenAccount
+      );
+      const tokenManager = await borrower.program.account.tokenManager.fetch(
+        borrower.tokenManager
+      );
+
+      assert.equal(loanAccount, null);
+      assert.ok(afterBorrowerBalance > beforeBorrowerBalance);
+      assert.equal(tokenAccount.isFrozen, false);
+      assert.equal(tokenManager.accounts.loan, false);
+    });
+
+    it("Freezes the token again when a cranked listing is re-listed", async () => {
+      const options = {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 30 * 24 * 60 * 60, // 30 days
+      };
+      const borrower = await helpers.initLoan(connection, {
+        ...options,
+        listingExpiry: Math.round(Date.now() / 1000) + 1,
+      });
+      await helpers.wait(3);
+
+      const keypair = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, keypair.publicKey);
+      const program = helpers.getProgram(
+        helpers.getProvider(connection, keypair)
+      );
+
+      // Without the borrower's signature the delegation is left in place
+      await program.methods
+        .closeExpiredListing()
+        .accounts({
+          signer: keypair.publicKey,
+          creator: borrower.keypair.publicKey,
+          listing: borrower.loanAccount,
+          depositTokenAccount: borrower.depositTokenAccount,
+          tokenManager: borrower.tokenManager,
+          mint: borrower.mint,
+          edition: borrower.edition,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+
+      let tokenAccount = await splToken.getAccount(
+        connection,
+        borrower.depositTokenAccount
+      );
+      assert.equal(tokenAccount.isFrozen, false);
+      assert.equal(
+        tokenAccount.delegate.toBase58(),
+        borrower.tokenManager.toBase58()
+      );
+
+      await borrower.program.methods
+        .initLoan({
+          amount: new anchor.BN(options.amount),
+          basisPoints: new anchor.BN(options.basisPoints),
+          duration: new anchor.BN(options.duration),
+          interestMode: { fullTerm: {} },
+          minimumFee: new anchor.BN(0),
+          currencyMint: null,
+          liquidationThreshold: null,
+          priceFeed: null,
+          gracePeriod: new anchor.BN(0),
+          lateFeeBasisPoints: 0,
+          defaultMode: { repossess: {} },
+          noticePeriod: null,
+          listingExpiry: null,
+          counterparty: null,
+        })
+        .accounts({
+          tokenManager: borrower.tokenManager,
+          depositTokenAccount: borrower.depositTokenAccount,
+          loan: borrower.loanAccount,
+          mint: borrower.mint,
+          borrower: borrower.keypair.publicKey,
+          edition: borrower.edition,
+          metadataProgram: METADATA_PROGRAM_ID,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      tokenAccount = await splToken.getAccount(
+        connection,
+        borrower.depositTokenAccount
+      );
+      assert.equal(tokenAccount.isFrozen, true);
+      assert.equal(
+        tokenAccount.delegate.toBase58(),
+        borrower.tokenManager.toBase58()
+      );
+    });
+
+    it("Does not close a call option listing before it expires", async () => {
+      const seller = await helpers.initCallOption(connection, {
+        amount: 1_000_000,
+        strikePrice: anchor.web3.LAMPORTS_PER_SOL,
+        expiry: Math.round(Date.now() / 1000) + 30 * 24 * 60 * 60, // 30 days
+        listingExpiry: Math.round(Date.now() / 1000) + 24 * 60 * 60, // 1 day
+      });
+
+      try {
+        await seller.program.methods
+          .closeExpiredListing()
+          .accounts({
+            signer: seller.keypair.publicKey,
+            creator: seller.keypair.publicKey,
+            listing: seller.callOptionAccount,
+            depositTokenAccount: seller.depositTokenAccount,
+            tokenManager: seller.tokenManager,
+            mint: seller.mint,
+            edition: seller.edition,
+            metadataProgram: METADATA_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            tokenProgram: splToken.TOKEN_PROGRAM_ID,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          })
+          .rpc();
+        assert.ok(false);
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "ListingNotExpired");
+      }
+    });
+  });
+
+  describe("Private listings", () => {
+    it("Only allows the counterparty to fund a private loan", async () => {
+      const counterparty = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, counterparty.publicKey);
+
+      const borrower = await helpers.initLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 30 * 24 * 60 * 60, // 30 days
+        counterparty: counterparty.publicKey,
       });
 
-      it("Allows loans to be repaid", async () => {
-        const borrower = await helpers.initLoan(connection, options);
-        const lender = await helpers.giveLoan(connection, borrower);
-        const lenderPreRepaymentBalance = await connection.getBalance(
-          lender.keypair.publicKey
-        );
+      try {
+        await helpers.giveLoan(connection, borrower);
+        assert.ok(false);
+      } catch (error) {
+        assert.ok(error.toString().includes("A raw constraint was violated"));
+      }
+
+      const program = helpers.getProgram(
+        helpers.getProvider(connection, counterparty)
+      );
+
+      await program.methods
+        .giveLoan()
+        .accounts({
+          borrower: borrower.keypair.publicKey,
+          lender: counterparty.publicKey,
+          loan: borrower.loanAccount,
+          tokenManager: borrower.tokenManager,
+          mint: borrower.mint,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+
+      const loan = await borrower.program.account.loan.fetch(
+        borrower.loanAccount
+      );
+      assert.deepEqual(loan.state, { active: {} });
+      assert.equal(loan.lender.toBase58(), counterparty.publicKey.toBase58());
+    });
 
-        await borrower.program.methods
-          .repayLoan()
+    it("Only allows the counterparty to buy a private call option", async () => {
+      const seller = await helpers.initCallOption(connection, {
+        amount: 1_000_000,
+        strikePrice: anchor.web3.LAMPORTS_PER_SOL,
+        expiry: Math.round(Date.now() / 1000) + 30 * 24 * 60 * 60, // 30 days
+        counterparty: anchor.web3.Keypair.generate().publicKey,
+      });
+
+      try {
+        await helpers.buyCallOption(connection, seller);
+        assert.ok(false);
+      } catch (error) {
+        assert.ok(error.toString().includes("A raw constraint was violated"));
+      }
+    });
+  });
+
+  describe("Expected terms", () => {
+    it("Rejects a loan whose terms have changed", async () => {
+      const options = {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 30 * 24 * 60 * 60, // 30 days
+      };
+      const borrower = await helpers.initLoan(connection, options);
+
+      const lender = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, lender.publicKey);
+      const program = helpers.getProgram(
+        helpers.getProvider(connection, lender)
+      );
+
+      const terms = {
+        amount: new anchor.BN(options.amount),
+        basisPoints: options.basisPoints,
+        duration: new anchor.BN(options.duration),
+        interestMode: { fullTerm: {} },
+        minimumFee: new anchor.BN(0),
+        currencyMint: null,
+        liquidationThreshold: null,
+        priceFeed: null,
+        gracePeriod: new anchor.BN(0),
+        lateFeeBasisPoints: 0,
+        defaultMode: { repossess: {} },
+        noticePeriod: null,
+        listingExpiry: null,
+        counterparty: null,
+      };
+
+      function giveLoanWithTerms(expectedTerms: typeof terms) {
+        return program.methods
+          .giveLoanWithTerms(expectedTerms)
           .accounts({
+            borrower: borrower.keypair.publicKey,
+            lender: lender.publicKey,
             loan: borrower.loanAccount,
             tokenManager: borrower.tokenManager,
-            borrower: borrower.keypair.publicKey,
-            depositTokenAccount: borrower.depositTokenAccount,
-            lender: lender.keypair.publicKey,
             mint: borrower.mint,
-            edition: borrower.edition,
-            metadataProgram: METADATA_PROGRAM_ID,
             systemProgram: anchor.web3.SystemProgram.programId,
             tokenProgram: splToken.TOKEN_PROGRAM_ID,
             clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
           })
           .rpc();
+      }
 
-        const lenderPostRepaymentBalance = await connection.getBalance(
-          lender.keypair.publicKey
-        );
-        const borrowerTokenAccount = await splToken.getAccount(
+      try {
+        await giveLoanWithTerms({
+          ...terms,
+          amount: new anchor.BN(options.amount / 2),
+        });
+        assert.ok(false);
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "TermsMismatch");
+      }
+
+      try {
+        await giveLoanWithTerms({
+          ...terms,
+          interestMode: { proRata: {} },
+        });
+        assert.ok(false);
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "TermsMismatch");
+      }
+
+      await giveLoanWithTerms(terms);
+
+      const loan = await borrower.program.account.loan.fetch(
+        borrower.loanAccount
+      );
+      assert.deepEqual(loan.state, { active: {} });
+    });
+  });
+
+  describe("Default auctions", () => {
+    let borrower: helpers.LoanBorrower;
+    let lender: helpers.LoanLender;
+    let lenderTokenAccount: anchor.web3.PublicKey;
+
+    function repossess() {
+      return lender.program.methods
+        .repossess()
+        .accounts({
+          borrower: borrower.keypair.publicKey,
+          depositTokenAccount: borrower.depositTokenAccount,
+          lender: lender.keypair.publicKey,
+          lenderTokenAccount,
+          loan: borrower.loanAccount,
+          tokenManager: borrower.tokenManager,
+          mint: borrower.mint,
+          edition: borrower.edition,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+    }
+
+    it("Starts an auction instead of repossessing", async () => {
+      borrower = await helpers.initLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 1,
+        defaultMode: {
+          auction: {
+            startPrice: new anchor.BN(3 * anchor.web3.LAMPORTS_PER_SOL),
+            floorPrice: new anchor.BN(2 * anchor.web3.LAMPORTS_PER_SOL),
+            duration: new anchor.BN(60 * 60), // 1 hour
+          },
+        },
+      });
+      lender = await helpers.giveLoan(connection, borrower);
+      await helpers.wait(2);
+
+      const tokenAccount = await splToken.getOrCreateAssociatedTokenAccount(
+        connection,
+        lender.keypair,
+        borrower.mint,
+        lender.keypair.publicKey
+      );
+      lenderTokenAccount = tokenAccount.address;
+
+      await repossess();
+
+      const loan = await borrower.program.account.loan.fetch(
+        borrower.loanAccount
+      );
+      const tokenManager = await borrower.program.account.tokenManager.fetch(
+        borrower.tokenManager
+      );
+      const depositTokenAccount = await splToken.getAccount(
+        connection,
+        borrower.depositTokenAccount
+      );
+
+      assert.deepEqual(loan.state, { auctioning: {} });
+      assert.ok(loan.auctionStart.toNumber() > 0);
+      assert.equal(tokenManager.accounts.loan, true);
+      assert.equal(depositTokenAccount.amount, BigInt(1));
+      assert.equal(depositTokenAccount.isFrozen, true);
+    });
+
+    it("Does not allow the lender to claim the NFT during the auction", async () => {
+      try {
+        await repossess();
+        assert.ok(false);
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "AuctionNotEnded");
+      }
+    });
+
+    it("Sells the NFT to a buyer", async () => {
+      const buyer = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, buyer.publicKey);
+      const program = helpers.getProgram(
+        helpers.getProvider(connection, buyer)
+      );
+
+      const [metadataAddress] = await helpers.findMetadataAddress(
+        borrower.mint
+      );
+      const metadata = await Metadata.fromAccountAddress(
+        connection,
+        metadataAddress
+      );
+      const buyerTokenAccount = await splToken.getAssociatedTokenAddress(
+        borrower.mint,
+        buyer.publicKey
+      );
+
+      const beforeLenderBalance = await connection.getBalance(
+        lender.keypair.publicKey
+      );
+      const beforeBorrowerBalance = await connection.getBalance(
+        borrower.keypair.publicKey
+      );
+
+      await program.methods
+        .buyLoanCollateral()
+        .accounts({
+          protocolConfig: await helpers.findProtocolConfigAddress(),
+          treasury: helpers.treasury.publicKey,
+          buyer: buyer.publicKey,
+          borrower: borrower.keypair.publicKey,
+          lender: lender.keypair.publicKey,
+          buyerTokenAccount,
+          depositTokenAccount: borrower.depositTokenAccount,
+          loan: borrower.loanAccount,
+          tokenManager: borrower.tokenManager,
+          mint: borrower.mint,
+          edition: borrower.edition,
+          metadata: metadataAddress,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: splToken.ASSOCIATED_TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .remainingAccounts(
+          metadata.data.creators.map((creator) => ({
+            pubkey: creator.address,
+            isSigner: false,
+            isWritable: true,
+          }))
+        )
+        .rpc();
+
+      const afterLenderBalance = await connection.getBalance(
+        lender.keypair.publicKey
+      );
+      const afterBorrowerBalance = await connection.getBalance(
+        borrower.keypair.publicKey
+      );
+      const loan = await borrower.program.account.loan.fetch(
+        borrower.loanAccount
+      );
+      const tokenAccount = await splToken.getAccount(
+        connection,
+        buyerTokenAccount
+      );
+
+      assert.deepEqual(loan.state, { defaulted: {} });
+      assert.equal(tokenAccount.amount, BigInt(1));
+      assert.equal(tokenAccount.isFrozen, false);
+      // The lender is repaid in full and the surplus returned to the borrower
+      assert.ok(
+        afterLenderBalance - beforeLenderBalance > anchor.web3.LAMPORTS_PER_SOL
+      );
+      assert.ok(afterBorrowerBalance > beforeBorrowerBalance);
+    });
+  });
+
+  describe("Bundle loans", () => {
+    it("Freezes each NFT in the bundle and thaws them on repayment", async () => {
+      const options = {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 30 * 24 * 60 * 60, // 30 days
+        count: 2,
+      };
+      const borrower = await helpers.initBundleLoan(connection, options);
+
+      for (const nft of borrower.nfts) {
+        const tokenAccount = await splToken.getAccount(
           connection,
-          borrower.depositTokenAccount
+          nft.depositTokenAccount
         );
         const tokenManager = await borrower.program.account.tokenManager.fetch(
-          borrower.tokenManager
+          nft.tokenManager
         );
-
+        assert.equal(tokenAccount.isFrozen, true);
+        assert.equal(tokenAccount.delegate.toBase58(), nft.tokenManager.toBase58());
         assert.deepEqual(tokenManager.accounts, {
           hire: false,
           callOption: false,
           loan: false,
         });
-        assert.equal(borrowerTokenAccount.amount, BigInt(1));
-        assert.equal(borrowerTokenAccount.delegate, null);
-        assert(lenderPostRepaymentBalance > lenderPreRepaymentBalance);
-      });
-
-      it("Prevents reinitialization", async () => {
-        const amount = anchor.web3.LAMPORTS_PER_SOL;
-        const basisPoints = 500;
-        const duration = 60;
+        assert.equal(tokenManager.bundle, true);
+      }
+
+      const lender = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, lender.publicKey);
+      const lenderProgram = helpers.getProgram(
+        helpers.getProvider(connection, lender)
+      );
+
+      await lenderProgram.methods
+        .giveBundleLoan()
+        .accounts({
+          borrower: borrower.keypair.publicKey,
+          lender: lender.publicKey,
+          bundleLoan: borrower.bundleLoan,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+
+      const bundleLoan = await borrower.program.account.bundleLoan.fetch(
+        borrower.bundleLoan
+      );
+      assert.deepEqual(bundleLoan.state, { active: {} });
+      assert.equal(bundleLoan.mints.length, options.count);
+
+      await borrower.program.methods
+        .repayBundleLoan()
+        .accounts({
+          protocolConfig: await helpers.findProtocolConfigAddress(),
+          treasury: helpers.treasury.publicKey,
+          borrower: borrower.keypair.publicKey,
+          lender: lender.publicKey,
+          bundleLoan: borrower.bundleLoan,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(helpers.getBundleRemainingAccounts(borrower.nfts))
+        .rpc();
+
+      for (const nft of borrower.nfts) {
+        const tokenAccount = await splToken.getAccount(
+          connection,
+          nft.depositTokenAccount
+        );
+        assert.equal(tokenAccount.isFrozen, false);
+        assert.equal(tokenAccount.delegate, null);
+      }
 
-        const borrower = await helpers.initLoan(connection, {
-          amount,
-          basisPoints,
-          duration,
-        });
-        await helpers.giveLoan(connection, borrower);
+      const bundleLoanAccount = await connection.getAccountInfo(
+        borrower.bundleLoan
+      );
+      assert.equal(bundleLoanAccount, null);
+    });
 
-        try {
-          await borrower.program.methods
-            .initLoan(
-              new anchor.BN(amount),
-              new anchor.BN(basisPoints),
-              new anchor.BN(1)
-            )
-            .accounts({
-              loan: borrower.loanAccount,
-              tokenManager: borrower.tokenManager,
-              depositTokenAccount: borrower.depositTokenAccount,
-              mint: borrower.mint,
-              borrower: borrower.keypair.publicKey,
-              edition: borrower.edition,
-              metadataProgram: METADATA_PROGRAM_ID,
-              tokenProgram: splToken.TOKEN_PROGRAM_ID,
-              rent: anchor.web3.SYSVAR_RENT_PUBKEY,
-              systemProgram: anchor.web3.SystemProgram.programId,
-            })
-            .rpc();
-          assert.fail();
-        } catch (error) {
-          assert.ok(error.toString().includes("custom program error: 0x0"));
-        }
+    it("Transfers every NFT in the bundle on repossession", async () => {
+      const borrower = await helpers.initBundleLoan(connection, {
+        amount: anchor.web3.LAMPORTS_PER_SOL,
+        basisPoints: 500,
+        duration: 1,
+        count: 2,
       });
+
+      const lender = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, lender.publicKey);
+      const lenderProgram = helpers.getProgram(
+        helpers.getProvider(connection, lender)
+      );
+
+      await lenderProgram.methods
+        .giveBundleLoan()
+        .accounts({
+          borrower: borrower.keypair.publicKey,
+          lender: lender.publicKey,
+          bundleLoan: borrower.bundleLoan,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+
+      await helpers.wait(2);
+
+      const remainingAccounts = [];
+      const lenderTokenAccounts = [];
+
+      for (const nft of borrower.nfts) {
+        const tokenAccount = await splToken.getOrCreateAssociatedTokenAccount(
+          connection,
+          lender,
+          nft.mint,
+          lender.publicKey
+        );
+        lenderTokenAccounts.push(tokenAccount.address);
+        remainingAccounts.push(
+          ...helpers.getBundleRemainingAccounts([nft]),
+          { pubkey: tokenAccount.address, isSigner: false, isWritable: true }
+        );
+      }
+
+      await lenderProgram.methods
+        .repossessBundleLoan()
+        .accounts({
+          borrower: borrower.keypair.publicKey,
+          lender: lender.publicKey,
+          bundleLoan: borrower.bundleLoan,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .remainingAccounts(remainingAccounts)
+        .rpc();
+
+      const bundleLoan = await borrower.program.account.bundleLoan.fetch(
+        borrower.bundleLoan
+      );
+      assert.deepEqual(bundleLoan.state, { defaulted: {} });
+
+      for (const address of lenderTokenAccounts) {
+        const tokenAccount = await splToken.getAccount(connection, address);
+        assert.equal(tokenAccount.amount, BigInt(1));
+      }
     });
   });
 
@@ -669,6 +2917,8 @@ describe("dexloan_listings", () => {
           const signature = await buyer.program.methods
             .exerciseCallOption()
             .accounts({
+              protocolConfig: await helpers.findProtocolConfigAddress(),
+              treasury: helpers.treasury.publicKey,
               seller: seller.keypair.publicKey,
               buyer: buyer.keypair.publicKey,
               callOption: seller.callOptionAccount,
@@ -775,6 +3025,53 @@ describe("dexloan_listings", () => {
       });
     });
 
+    describe("Update call option", () => {
+      it("Allows listed call options to be updated", async () => {
+        const seller = await helpers.initCallOption(connection, {
+          amount: 1_000_000,
+          strikePrice: anchor.web3.LAMPORTS_PER_SOL,
+          expiry: Math.round(Date.now() / 1000) + 30 * 24 * 60 * 60, // 30 days
+        });
+        const amount = new anchor.BN(2_000_000);
+        const strikePrice = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL * 2);
+        const expiry = new anchor.BN(
+          Math.round(Date.now() / 1000) + 60 * 24 * 60 * 60 // 60 days
+        );
+
+        await seller.program.methods
+          .updateCallOption({
+            amount,
+            strikePrice,
+            expiry,
+            listingExpiry: null,
+            counterparty: null,
+            exerciseStyle: { american: {} },
+            currencyMint: null,
+          })
+          .accounts({
+            seller: seller.keypair.publicKey,
+            callOption: seller.callOptionAccount,
+            mint: seller.mint,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          })
+          .rpc();
+
+        const callOption = await seller.program.account.callOption.fetch(
+          seller.callOptionAccount
+        );
+        const tokenAccount = await splToken.getAccount(
+          connection,
+          seller.depositTokenAccount
+        );
+
+        assert.equal(callOption.amount.toNumber(), amount.toNumber());
+        assert.equal(callOption.strikePrice.toNumber(), strikePrice.toNumber());
+        assert.equal(callOption.expiry.toNumber(), expiry.toNumber());
+        assert.deepEqual(callOption.state, { listed: {} });
+        assert.ok(tokenAccount.isFrozen);
+      });
+    });
+
     describe("Call option expiry", () => {
       let options;
       let seller: Awaited<ReturnType<typeof helpers.initCallOption>>;
@@ -856,6 +3153,8 @@ describe("dexloan_listings", () => {
           await buyer.program.methods
             .exerciseCallOption()
             .accounts({
+              protocolConfig: await helpers.findProtocolConfigAddress(),
+              treasury: helpers.treasury.publicKey,
               seller: seller.keypair.publicKey,
               buyer: buyer.keypair.publicKey,
               buyerTokenAccount: tokenAccount.address,
@@ -913,7 +3212,793 @@ describe("dexloan_listings", () => {
     });
   });
 
-  describe.only("Hires", () => {
+  describe("Exercise styles", () => {
+    async function exercise(
+      seller: helpers.CallOptionSeller,
+      buyer: helpers.CallOptionBuyer
+    ) {
+      const tokenAccount = await splToken.getOrCreateAssociatedTokenAccount(
+        connection,
+        buyer.keypair,
+        seller.mint,
+        buyer.keypair.publicKey
+      );
+      const [metadataAddress] = await helpers.findMetadataAddress(seller.mint);
+      const metadata = await Metadata.fromAccountAddress(
+        connection,
+        metadataAddress
+      );
+
+      return buyer.program.methods
+        .exerciseCallOption()
+        .accounts({
+          protocolConfig: await helpers.findProtocolConfigAddress(),
+          treasury: helpers.treasury.publicKey,
+          seller: seller.keypair.publicKey,
+          buyer: buyer.keypair.publicKey,
+          callOption: seller.callOptionAccount,
+          tokenManager: seller.tokenManager,
+          buyerTokenAccount: tokenAccount.address,
+          depositTokenAccount: seller.depositTokenAccount,
+          mint: seller.mint,
+          edition: seller.edition,
+          metadata: metadataAddress,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .remainingAccounts(
+          metadata.data.creators.map((creator) => ({
+            pubkey: creator.address,
+            isSigner: false,
+            isWritable: true,
+          }))
+        )
+        .rpc();
+    }
+
+    it("Does not allow a european option to be exercised before its window", async () => {
+      const seller = await helpers.initCallOption(connection, {
+        amount: 1_000_000,
+        strikePrice: anchor.web3.LAMPORTS_PER_SOL,
+        expiry: Math.round(Date.now() / 1000) + 30 * 24 * 60 * 60, // 30 days
+        exerciseStyle: {
+          european: { window: new anchor.BN(24 * 60 * 60) }, // 1 day
+        },
+      });
+      const buyer = await helpers.buyCallOption(connection, seller);
+
+      try {
+        await exercise(seller, buyer);
+        assert.fail();
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "OptionNotExercisable");
+      }
+    });
+
+    it("Allows a bermudan option to be exercised within a window", async () => {
+      const now = Math.round(Date.now() / 1000);
+      const seller = await helpers.initCallOption(connection, {
+        amount: 1_000_000,
+        strikePrice: anchor.web3.LAMPORTS_PER_SOL,
+        expiry: now + 30 * 24 * 60 * 60, // 30 days
+        exerciseStyle: {
+          bermudan: {
+            windows: [
+              {
+                start: new anchor.BN(now - 60),
+                end: new anchor.BN(now + 60 * 60),
+              },
+            ],
+          },
+        },
+      });
+      const buyer = await helpers.buyCallOption(connection, seller);
+
+      await exercise(seller, buyer);
+
+      const callOption = await seller.program.account.callOption.fetch(
+        seller.callOptionAccount
+      );
+      assert.deepEqual(callOption.state, { exercised: {} });
+    });
+
+    it("Does not allow bermudan windows after expiry", async () => {
+      const now = Math.round(Date.now() / 1000);
+
+      try {
+        await helpers.initCallOption(connection, {
+          amount: 1_000_000,
+          strikePrice: anchor.web3.LAMPORTS_PER_SOL,
+          expiry: now + 24 * 60 * 60, // 1 day
+          exerciseStyle: {
+            bermudan: {
+              windows: [
+                {
+                  start: new anchor.BN(now),
+                  end: new anchor.BN(now + 2 * 24 * 60 * 60),
+                },
+              ],
+            },
+          },
+        });
+        assert.fail();
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "InvalidExpiry");
+      }
+    });
+  });
+
+  describe("Call option positions", () => {
+    let seller: helpers.CallOptionSeller;
+    let buyer: helpers.CallOptionBuyer;
+    let newBuyer: anchor.web3.Keypair;
+
+    const price = anchor.web3.LAMPORTS_PER_SOL / 10;
+
+    async function buyPosition(
+      keypair: anchor.web3.Keypair,
+      expectedPrice: number = price
+    ) {
+      const program = helpers.getProgram(
+        helpers.getProvider(connection, keypair)
+      );
+
+      return program.methods
+        .buyCallOptionPosition(new anchor.BN(expectedPrice))
+        .accounts({
+          seller: seller.keypair.publicKey,
+          buyer: buyer.keypair.publicKey,
+          newBuyer: keypair.publicKey,
+          callOption: seller.callOptionAccount,
+          protocolConfig: await helpers.findProtocolConfigAddress(),
+          treasury: helpers.treasury.publicKey,
+          mint: seller.mint,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+    }
+
+    it("Does not allow buying an unlisted position", async () => {
+      seller = await helpers.initCallOption(connection, {
+        amount: 1_000_000,
+        strikePrice: anchor.web3.LAMPORTS_PER_SOL,
+        expiry: Math.round(Date.now() / 1000) + 30 * 24 * 60 * 60, // 30 days
+      });
+      buyer = await helpers.buyCallOption(connection, seller);
+      newBuyer = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, newBuyer.publicKey);
+
+      try {
+        await buyPosition(newBuyer);
+        assert.ok(false);
+      } catch (error) {
+        assert.ok(
+          error.toString().includes("A raw constraint was violated")
+        );
+      }
+    });
+
+    it("Sells a call option position", async () => {
+      await buyer.program.methods
+        .listCallOptionPosition(new anchor.BN(price))
+        .accounts({
+          seller: seller.keypair.publicKey,
+          buyer: buyer.keypair.publicKey,
+          callOption: seller.callOptionAccount,
+          mint: seller.mint,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+
+      try {
+        await buyPosition(newBuyer, price / 2);
+        assert.ok(false);
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "TermsMismatch");
+      }
+
+      const beforeBuyerBalance = await connection.getBalance(
+        buyer.keypair.publicKey
+      );
+
+      await buyPosition(newBuyer);
+
+      const afterBuyerBalance = await connection.getBalance(
+        buyer.keypair.publicKey
+      );
+      const callOption = await seller.program.account.callOption.fetch(
+        seller.callOptionAccount
+      );
+
+      assert.equal(afterBuyerBalance - beforeBuyerBalance, price);
+      assert.equal(callOption.buyer.toBase58(), newBuyer.publicKey.toBase58());
+      assert.equal(callOption.positionPrice, null);
+      assert.deepEqual(callOption.state, { active: {} });
+    });
+  });
+
+  describe("Call option buy backs", () => {
+    let seller: helpers.CallOptionSeller;
+    let buyer: helpers.CallOptionBuyer;
+
+    const price = anchor.web3.LAMPORTS_PER_SOL / 10;
+
+    async function buyBack(expectedPrice: number = price) {
+      return seller.program.methods
+        .buyBackCallOption(new anchor.BN(expectedPrice))
+        .accounts({
+          seller: seller.keypair.publicKey,
+          buyer: buyer.keypair.publicKey,
+          callOption: seller.callOptionAccount,
+          tokenManager: seller.tokenManager,
+          depositTokenAccount: seller.depositTokenAccount,
+          protocolConfig: await helpers.findProtocolConfigAddress(),
+          treasury: helpers.treasury.publicKey,
+          mint: seller.mint,
+          edition: seller.edition,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+    }
+
+    it("Does not allow a buy back without an asking price", async () => {
+      seller = await helpers.initCallOption(connection, {
+        amount: 1_000_000,
+        strikePrice: anchor.web3.LAMPORTS_PER_SOL,
+        expiry: Math.round(Date.now() / 1000) + 30 * 24 * 60 * 60, // 30 days
+      });
+      buyer = await helpers.buyCallOption(connection, seller);
+
+      try {
+        await buyBack();
+        assert.ok(false);
+      } catch (error) {
+        assert.ok(
+          error.toString().includes("A raw constraint was violated")
+        );
+      }
+    });
+
+    it("Allows the seller to buy back an option", async () => {
+      await buyer.program.methods
+        .listCallOptionBuyBack(new anchor.BN(price))
+        .accounts({
+          seller: seller.keypair.publicKey,
+          buyer: buyer.keypair.publicKey,
+          callOption: seller.callOptionAccount,
+          mint: seller.mint,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+
+      try {
+        await buyBack(price / 2);
+        assert.ok(false);
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "TermsMismatch");
+      }
+
+      const beforeBuyerBalance = await connection.getBalance(
+        buyer.keypair.publicKey
+      );
+
+      await buyBack();
+
+      const afterBuyerBalance = await connection.getBalance(
+        buyer.keypair.publicKey
+      );
+      const tokenManager = await seller.program.account.tokenManager.fetch(
+        seller.tokenManager
+      );
+      const sellerTokenAccount = await splToken.getAccount(
+        connection,
+        seller.depositTokenAccount
+      );
+
+      assert.equal(afterBuyerBalance - beforeBuyerBalance, price);
+      assert.equal(tokenManager.accounts.callOption, false);
+      assert.equal(sellerTokenAccount.isFrozen, false);
+      assert.equal(sellerTokenAccount.delegate, null);
+
+      try {
+        await seller.program.account.callOption.fetch(
+          seller.callOptionAccount
+        );
+        assert.fail();
+      } catch (error) {
+        assert.ok(error.message.includes("Account does not exist"));
+      }
+    });
+  });
+
+  describe("Call option auctions", () => {
+    const reservePrice = 1_000_000;
+
+    async function startAuction(
+      seller: helpers.CallOptionSeller,
+      duration: number
+    ) {
+      await seller.program.methods
+        .startCallOptionAuction(new anchor.BN(duration))
+        .accounts({
+          seller: seller.keypair.publicKey,
+          callOption: seller.callOptionAccount,
+          callOptionBid: await helpers.findCallOptionBidAddress(
+            seller.callOptionAccount
+          ),
+          mint: seller.mint,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+    }
+
+    async function bid(
+      seller: helpers.CallOptionSeller,
+      bidder: anchor.web3.Keypair,
+      previousBidder: anchor.web3.PublicKey,
+      amount: number
+    ) {
+      const program = helpers.getProgram(
+        helpers.getProvider(connection, bidder)
+      );
+
+      await program.methods
+        .bidCallOption(new anchor.BN(amount))
+        .accounts({
+          seller: seller.keypair.publicKey,
+          bidder: bidder.publicKey,
+          previousBidder,
+          callOption: seller.callOptionAccount,
+          callOptionBid: await helpers.findCallOptionBidAddress(
+            seller.callOptionAccount
+          ),
+          mint: seller.mint,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+    }
+
+    async function settle(seller: helpers.CallOptionSeller) {
+      await seller.program.methods
+        .settleCallOptionAuction()
+        .accounts({
+          signer: seller.keypair.publicKey,
+          seller: seller.keypair.publicKey,
+          callOption: seller.callOptionAccount,
+          callOptionBid: await helpers.findCallOptionBidAddress(
+            seller.callOptionAccount
+          ),
+          protocolConfig: await helpers.findProtocolConfigAddress(),
+          treasury: helpers.treasury.publicKey,
+          mint: seller.mint,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+    }
+
+    it("Refunds outbid buyers and settles to the highest bidder", async () => {
+      const seller = await helpers.initCallOption(connection, {
+        amount: reservePrice,
+        strikePrice: anchor.web3.LAMPORTS_PER_SOL,
+        expiry: Math.round(Date.now() / 1000) + 30 * 24 * 60 * 60, // 30 days
+      });
+      await startAuction(seller, 10);
+
+      const firstBidder = anchor.web3.Keypair.generate();
+      const secondBidder = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, firstBidder.publicKey);
+      await helpers.requestAirdrop(connection, secondBidder.publicKey);
+
+      try {
+        await bid(seller, firstBidder, firstBidder.publicKey, reservePrice - 1);
+        assert.fail();
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "InvalidAmount");
+      }
+
+      await bid(seller, firstBidder, firstBidder.publicKey, reservePrice);
+      const beforeFirstBidderBalance = await connection.getBalance(
+        firstBidder.publicKey
+      );
+      await bid(seller, secondBidder, firstBidder.publicKey, reservePrice * 2);
+      const afterFirstBidderBalance = await connection.getBalance(
+        firstBidder.publicKey
+      );
+
+      assert.equal(
+        afterFirstBidderBalance - beforeFirstBidderBalance,
+        reservePrice
+      );
+
+      try {
+        await settle(seller);
+        assert.fail();
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "AuctionNotEnded");
+      }
+
+      await helpers.wait(11);
+
+      const beforeSellerBalance = await connection.getBalance(
+        seller.keypair.publicKey
+      );
+      await settle(seller);
+      const afterSellerBalance = await connection.getBalance(
+        seller.keypair.publicKey
+      );
+
+      const callOption = await seller.program.account.callOption.fetch(
+        seller.callOptionAccount
+      );
+
+      assert.ok(afterSellerBalance - beforeSellerBalance > reservePrice * 2);
+      assert.deepEqual(callOption.state, { active: {} });
+      assert.equal(
+        callOption.buyer.toBase58(),
+        secondBidder.publicKey.toBase58()
+      );
+      assert.equal(callOption.amount.toNumber(), reservePrice * 2);
+    });
+
+    it("Reverts to listed when there are no bids", async () => {
+      const seller = await helpers.initCallOption(connection, {
+        amount: reservePrice,
+        strikePrice: anchor.web3.LAMPORTS_PER_SOL,
+        expiry: Math.round(Date.now() / 1000) + 30 * 24 * 60 * 60, // 30 days
+      });
+      await startAuction(seller, 1);
+      await helpers.wait(2);
+      await settle(seller);
+
+      const callOption = await seller.program.account.callOption.fetch(
+        seller.callOptionAccount
+      );
+
+      assert.deepEqual(callOption.state, { listed: {} });
+    });
+  });
+
+  describe("Token denominated call options", () => {
+    it("Pays the premium, strike and royalties as SPL tokens", async () => {
+      const options = {
+        amount: 1_000_000,
+        strikePrice: 100_000_000,
+        expiry: Math.round(Date.now() / 1000) + 30 * 24 * 60 * 60, // 30 days
+      };
+
+      const buyerKeypair = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, buyerKeypair.publicKey);
+      const currencyMint = await splToken.createMint(
+        connection,
+        buyerKeypair,
+        buyerKeypair.publicKey,
+        null,
+        6
+      );
+      const buyerCurrencyAccount =
+        await splToken.getOrCreateAssociatedTokenAccount(
+          connection,
+          buyerKeypair,
+          currencyMint,
+          buyerKeypair.publicKey
+        );
+      await splToken.mintTo(
+        connection,
+        buyerKeypair,
+        currencyMint,
+        buyerCurrencyAccount.address,
+        buyerKeypair,
+        options.amount + options.strikePrice
+      );
+
+      const seller = await helpers.initCallOption(connection, {
+        ...options,
+        currencyMint,
+      });
+      const sellerCurrencyAccount =
+        await splToken.getOrCreateAssociatedTokenAccount(
+          connection,
+          buyerKeypair,
+          currencyMint,
+          seller.keypair.publicKey
+        );
+      const treasuryCurrencyAccount =
+        await splToken.getOrCreateAssociatedTokenAccount(
+          connection,
+          buyerKeypair,
+          currencyMint,
+          helpers.treasury.publicKey
+        );
+      const currencyAccounts = [
+        buyerCurrencyAccount.address,
+        sellerCurrencyAccount.address,
+        treasuryCurrencyAccount.address,
+      ].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+
+      const program = helpers.getProgram(
+        helpers.getProvider(connection, buyerKeypair)
+      );
+
+      await program.methods
+        .buyCallOption()
+        .accounts({
+          protocolConfig: await helpers.findProtocolConfigAddress(),
+          treasury: helpers.treasury.publicKey,
+          seller: seller.keypair.publicKey,
+          buyer: buyerKeypair.publicKey,
+          callOption: seller.callOptionAccount,
+          tokenManager: seller.tokenManager,
+          mint: seller.mint,
+          edition: seller.edition,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .remainingAccounts(currencyAccounts)
+        .rpc();
+
+      let sellerCurrencyBalance = await splToken.getAccount(
+        connection,
+        sellerCurrencyAccount.address
+      );
+      assert.equal(sellerCurrencyBalance.amount, BigInt(options.amount));
+
+      const [metadataAddress] = await helpers.findMetadataAddress(seller.mint);
+      const metadata = await Metadata.fromAccountAddress(
+        connection,
+        metadataAddress
+      );
+      const creatorCurrencyAccounts = [];
+      for (const creator of metadata.data.creators) {
+        const creatorCurrencyAccount =
+          await splToken.getOrCreateAssociatedTokenAccount(
+            connection,
+            buyerKeypair,
+            currencyMint,
+            creator.address
+          );
+        creatorCurrencyAccounts.push(creatorCurrencyAccount.address);
+      }
+      const buyerTokenAccount =
+        await splToken.getOrCreateAssociatedTokenAccount(
+          connection,
+          buyerKeypair,
+          seller.mint,
+          buyerKeypair.publicKey
+        );
+
+      await program.methods
+        .exerciseCallOption()
+        .accounts({
+          protocolConfig: await helpers.findProtocolConfigAddress(),
+          treasury: helpers.treasury.publicKey,
+          seller: seller.keypair.publicKey,
+          buyer: buyerKeypair.publicKey,
+          callOption: seller.callOptionAccount,
+          tokenManager: seller.tokenManager,
+          buyerTokenAccount: buyerTokenAccount.address,
+          depositTokenAccount: seller.depositTokenAccount,
+          mint: seller.mint,
+          edition: seller.edition,
+          metadata: metadataAddress,
+          metadataProgram: METADATA_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .remainingAccounts([
+          ...currencyAccounts,
+          ...creatorCurrencyAccounts.map((pubkey) => ({
+            pubkey,
+            isSigner: false,
+            isWritable: true,
+          })),
+        ])
+        .rpc();
+
+      sellerCurrencyBalance = await splToken.getAccount(
+        connection,
+        sellerCurrencyAccount.address
+      );
+      const creatorCurrencyBalance = await splToken.getAccount(
+        connection,
+        creatorCurrencyAccounts[0]
+      );
+      const royalties =
+        (options.strikePrice * metadata.data.sellerFeeBasisPoints) / 10_000;
+      const nftTokenAccount = await splToken.getAccount(
+        connection,
+        buyerTokenAccount.address
+      );
+
+      assert.equal(creatorCurrencyBalance.amount, BigInt(royalties));
+      assert.equal(
+        sellerCurrencyBalance.amount,
+        BigInt(options.amount + options.strikePrice - royalties)
+      );
+      assert.equal(nftTokenAccount.amount, BigInt(1));
+    });
+  });
+
+  describe("Put Options", () => {
+    it("Exercises a put option", async () => {
+      const buyer = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, buyer.publicKey);
+      const nft = await helpers.mintNFT(connection, buyer);
+
+      const options = {
+        amount: 1_000_000,
+        strikePrice: anchor.web3.LAMPORTS_PER_SOL,
+        expiry: Math.round(Date.now() / 1000) + 30 * 24 * 60 * 60, // 30 days
+      };
+      const writer = await helpers.initPutOption(
+        connection,
+        nft.mint.address,
+        options
+      );
+      await helpers.buyPutOption(connection, buyer, writer);
+
+      const [metadataAddress] = await helpers.findMetadataAddress(
+        nft.mint.address
+      );
+      const metadata = await Metadata.fromAccountAddress(
+        connection,
+        metadataAddress
+      );
+      const buyerTokenAccount = await splToken.getAssociatedTokenAddress(
+        nft.mint.address,
+        buyer.publicKey
+      );
+      const writerTokenAccount = await splToken.getAssociatedTokenAddress(
+        nft.mint.address,
+        writer.keypair.publicKey
+      );
+      const beforeBuyerBalance = await connection.getBalance(buyer.publicKey);
+
+      const program = helpers.getProgram(
+        helpers.getProvider(connection, buyer)
+      );
+
+      await program.methods
+        .exercisePutOption()
+        .accounts({
+          protocolConfig: await helpers.findProtocolConfigAddress(),
+          treasury: helpers.treasury.publicKey,
+          writer: writer.keypair.publicKey,
+          buyer: buyer.publicKey,
+          putOption: writer.putOptionAccount,
+          buyerTokenAccount,
+          writerTokenAccount,
+          mint: nft.mint.address,
+          metadata: metadataAddress,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: splToken.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: splToken.ASSOCIATED_TOKEN_PROGRAM_ID,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .remainingAccounts(
+          metadata.data.creators.map((creator) => ({
+            pubkey: creator.address,
+            isSigner: false,
+            isWritable: true,
+          }))
+        )
+        .rpc();
+
+      const afterBuyerBalance = await connection.getBalance(buyer.publicKey);
+      const putOption = await writer.program.account.putOption.fetch(
+        writer.putOptionAccount
+      );
+      const tokenAccount = await splToken.getAccount(
+        connection,
+        writerTokenAccount
+      );
+      const royalties = options.strikePrice * 0.05;
+
+      assert.deepEqual(putOption.state, { exercised: {} });
+      assert.equal(tokenAccount.amount, BigInt(1));
+      // The buyer also pays rent for the writer's token account
+      assert.ok(
+        afterBuyerBalance - beforeBuyerBalance >
+          options.strikePrice - royalties - 0.01 * anchor.web3.LAMPORTS_PER_SOL
+      );
+    });
+
+    it("Refunds the strike when the writer closes an unsold put option", async () => {
+      const holder = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, holder.publicKey);
+      const nft = await helpers.mintNFT(connection, holder);
+
+      const writer = await helpers.initPutOption(connection, nft.mint.address, {
+        amount: 1_000_000,
+        strikePrice: anchor.web3.LAMPORTS_PER_SOL,
+        expiry: Math.round(Date.now() / 1000) + 30 * 24 * 60 * 60, // 30 days
+      });
+      const beforeWriterBalance = await connection.getBalance(
+        writer.keypair.publicKey
+      );
+
+      await writer.program.methods
+        .closePutOption()
+        .accounts({
+          writer: writer.keypair.publicKey,
+          putOption: writer.putOptionAccount,
+          mint: nft.mint.address,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+
+      const afterWriterBalance = await connection.getBalance(
+        writer.keypair.publicKey
+      );
+      assert.ok(
+        afterWriterBalance - beforeWriterBalance >=
+          anchor.web3.LAMPORTS_PER_SOL - 10_000
+      );
+    });
+
+    it("Does not buy a put option when the terms have changed", async () => {
+      const holder = anchor.web3.Keypair.generate();
+      const buyer = anchor.web3.Keypair.generate();
+      await helpers.requestAirdrop(connection, holder.publicKey);
+      await helpers.requestAirdrop(connection, buyer.publicKey);
+      const nft = await helpers.mintNFT(connection, holder);
+
+      const options = {
+        amount: 1_000_000,
+        strikePrice: anchor.web3.LAMPORTS_PER_SOL,
+        expiry: Math.round(Date.now() / 1000) + 30 * 24 * 60 * 60, // 30 days
+      };
+      const writer = await helpers.initPutOption(
+        connection,
+        nft.mint.address,
+        options
+      );
+
+      try {
+        await helpers.buyPutOption(connection, buyer, writer, {
+          ...options,
+          amount: options.amount / 2,
+        });
+        assert.ok(false);
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "TermsMismatch");
+      }
+
+      try {
+        await helpers.buyPutOption(connection, buyer, writer, {
+          ...options,
+          strikePrice: options.strikePrice * 2,
+        });
+        assert.ok(false);
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "TermsMismatch");
+      }
+
+      await helpers.buyPutOption(connection, buyer, writer);
+
+      const putOption = await writer.program.account.putOption.fetch(
+        writer.putOptionAccount
+      );
+      assert.equal(putOption.buyer.toBase58(), buyer.publicKey.toBase58());
+    });
+  });
+
+  describe("Hires", () => {
     describe("Specified borrower", async () => {
       let lender: helpers.HireLender;
       let borrowerTokenAccount: anchor.web3.PublicKey;
@@ -972,6 +4057,8 @@ describe("dexloan_listings", () => {
           await program.methods
             .takeHire(1)
             .accounts({
+              protocolConfig: await helpers.findProtocolConfigAddress(),
+              treasury: helpers.treasury.publicKey,
               borrower: newKeypair.publicKey,
               lender: lender.keypair.publicKey,
               hire: lender.hire,
@@ -1027,6 +4114,8 @@ describe("dexloan_listings", () => {
         await program.methods
           .takeHire(days)
           .accounts({
+            protocolConfig: await helpers.findProtocolConfigAddress(),
+            treasury: helpers.treasury.publicKey,
             borrower: privateBorrower.publicKey,
             lender: lender.keypair.publicKey,
             hire: lender.hire,
@@ -1096,6 +4185,8 @@ describe("dexloan_listings", () => {
           await lender.program.methods
             .recoverHire()
             .accounts({
+              protocolConfig: await helpers.findProtocolConfigAddress(),
+              treasury: helpers.treasury.publicKey,
               borrower: privateBorrower.publicKey,
               lender: lender.keypair.publicKey,
               hire: lender.hire,
@@ -1155,6 +4246,38 @@ describe("dexloan_listings", () => {
         assert.deepEqual(hire.state, { listed: {} });
       });
 
+      it("Allows a listed hire to be updated", async () => {
+        options = {
+          amount: 20_000,
+          expiry: Math.round(Date.now() / 1000 + 86_400 * 90),
+        };
+
+        await lender.program.methods
+          .updateHire({
+            amount: new anchor.BN(options.amount),
+            expiry: new anchor.BN(options.expiry),
+            borrower: null,
+          })
+          .accounts({
+            lender: lender.keypair.publicKey,
+            hire: lender.hire,
+            mint: lender.mint,
+            clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+          })
+          .rpc();
+
+        const hire = await lender.program.account.hire.fetch(lender.hire);
+        const tokenAccount = await splToken.getAccount(
+          connection,
+          lender.depositTokenAccount
+        );
+
+        assert.equal(hire.amount.toNumber(), options.amount);
+        assert.equal(hire.expiry.toNumber(), options.expiry);
+        assert.deepEqual(hire.state, { listed: {} });
+        assert.ok(tokenAccount.isFrozen);
+      });
+
       it("Allows a hire to be taken for x days", async () => {
         const days = 2;
         const estimatedCurrentExpiry = Math.round(
@@ -1274,6 +4397,8 @@ describe("dexloan_listings", () => {
           await program.methods
             .takeHire(2)
             .accounts({
+              protocolConfig: await helpers.findProtocolConfigAddress(),
+              treasury: helpers.treasury.publicKey,
               borrower: thirdPartyKeypair.publicKey,
               lender: borrower.keypair.publicKey,
               hire: hireAddress,
@@ -1330,6 +4455,8 @@ describe("dexloan_listings", () => {
         await borrower.program.methods
           .repayLoan()
           .accounts({
+            protocolConfig: await helpers.findProtocolConfigAddress(),
+            treasury: helpers.treasury.publicKey,
             loan: borrower.loanAccount,
             tokenManager: borrower.tokenManager,
             borrower: borrower.keypair.publicKey,
@@ -1457,6 +4584,8 @@ describe("dexloan_listings", () => {
           await program.methods
             .takeHire(2)
             .accounts({
+              protocolConfig: await helpers.findProtocolConfigAddress(),
+              treasury: helpers.treasury.publicKey,
               borrower: thirdPartyKeypair.publicKey,
               lender: borrower.keypair.publicKey,
               hire: hireAddress,
@@ -1533,6 +4662,8 @@ describe("dexloan_listings", () => {
           await lender.program.methods
             .repossessWithHire()
             .accounts({
+              protocolConfig: await helpers.findProtocolConfigAddress(),
+              treasury: helpers.treasury.publicKey,
               hire: hireAddress,
               hireEscrow: hireEscrowAddress,
               borrower: borrower.keypair.publicKey,
@@ -1662,6 +4793,8 @@ describe("dexloan_listings", () => {
           await lender.program.methods
             .repossessWithHire()
             .accounts({
+              protocolConfig: await helpers.findProtocolConfigAddress(),
+              treasury: helpers.treasury.publicKey,
               hire: hireAddress,
               hireEscrow: hireEscrowAddress,
               borrower: borrower.keypair.publicKey,
@@ -1818,6 +4951,8 @@ describe("dexloan_listings", () => {
           await program.methods
             .takeHire(2)
             .accounts({
+              protocolConfig: await helpers.findProtocolConfigAddress(),
+              treasury: helpers.treasury.publicKey,
               hireTokenAccount,
               borrower: thirdPartyKeypair.publicKey,
               lender: seller.keypair.publicKey,
@@ -1913,6 +5048,8 @@ describe("dexloan_listings", () => {
           const signature = await buyer.program.methods
             .exerciseCallOptionWithHire()
             .accounts({
+              protocolConfig: await helpers.findProtocolConfigAddress(),
+              treasury: helpers.treasury.publicKey,
               seller: seller.keypair.publicKey,
               buyer: buyer.keypair.publicKey,
               callOption: seller.callOptionAccount,
@@ -44,6 +44,71 @@ export async function requestAirdrop(
   });
 }
 
+export const treasury = anchor.web3.Keypair.generate();
+
+export const oracleProgram = anchor.web3.Keypair.generate().publicKey;
+
+export async function findProtocolConfigAddress(): Promise<anchor.web3.PublicKey> {
+  const [protocolConfigAddress] =
+    await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("protocol_config")],
+      PROGRAM_ID
+    );
+
+  return protocolConfigAddress;
+}
+
+export async function findProgramDataAddress(): Promise<anchor.web3.PublicKey> {
+  const [programDataAddress] = await anchor.web3.PublicKey.findProgramAddress(
+    [PROGRAM_ID.toBuffer()],
+    new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
+  return programDataAddress;
+}
+
+export async function initProtocolConfig(connection: anchor.web3.Connection) {
+  // The program is deployed with the provider wallet as its upgrade authority
+  const keypair = (anchor.AnchorProvider.env().wallet as anchor.Wallet).payer;
+  const provider = getProvider(connection, keypair);
+  const program = getProgram(provider);
+  // Fund the treasury so that small fees are rent exempt
+  await requestAirdrop(connection, treasury.publicKey);
+
+  const protocolConfig = await findProtocolConfigAddress();
+
+  try {
+    await program.methods
+      .initProtocolConfig({
+        treasury: treasury.publicKey,
+        loanFeeBasisPoints: 0,
+        optionPremiumFeeBasisPoints: 0,
+        optionExerciseFeeBasisPoints: 0,
+        hireFeeBasisPoints: 0,
+        oracleProgram,
+      })
+      .accounts({
+        protocolConfig,
+        admin: keypair.publicKey,
+        program: PROGRAM_ID,
+        programData: await findProgramDataAddress(),
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+  } catch (error) {
+    console.log(error.logs);
+    throw error;
+  }
+
+  return {
+    keypair,
+    provider,
+    program,
+    protocolConfig,
+  };
+}
+
 export async function findListingAddress(
   mint: anchor.web3.PublicKey,
   borrower: anchor.web3.PublicKey
@@ -80,6 +145,19 @@ export async function findLoanAddress(
   return loanAddress;
 }
 
+export async function findLoanExtensionAddress(
+  mint: anchor.web3.PublicKey,
+  borrower: anchor.web3.PublicKey
+): Promise<anchor.web3.PublicKey> {
+  const [loanExtensionAddress] =
+    await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("loan_extension"), mint.toBuffer(), borrower.toBuffer()],
+      PROGRAM_ID
+    );
+
+  return loanExtensionAddress;
+}
+
 export async function findCallOptionAddress(
   mint: anchor.web3.PublicKey,
   seller: anchor.web3.PublicKey
@@ -92,6 +170,17 @@ export async function findCallOptionAddress(
   return callOptionAddress;
 }
 
+export async function findCallOptionBidAddress(
+  callOption: anchor.web3.PublicKey
+): Promise<anchor.web3.PublicKey> {
+  const [callOptionBidAddress] = await anchor.web3.PublicKey.findProgramAddress(
+    [Buffer.from("call_option_bid"), callOption.toBuffer()],
+    PROGRAM_ID
+  );
+
+  return callOptionBidAddress;
+}
+
 export async function findHireAddress(
   mint: anchor.web3.PublicKey,
   lender: anchor.web3.PublicKey
@@ -116,6 +205,18 @@ export async function findHireEscrowAddress(
   return hireEscrowAddress;
 }
 
+export async function findPutOptionAddress(
+  mint: anchor.web3.PublicKey,
+  writer: anchor.web3.PublicKey
+): Promise<anchor.web3.PublicKey> {
+  const [putOptionAddress] = await anchor.web3.PublicKey.findProgramAddress(
+    [Buffer.from("put_option"), mint.toBuffer(), writer.toBuffer()],
+    PROGRAM_ID
+  );
+
+  return putOptionAddress;
+}
+
 export async function findMetadataAddress(mint: anchor.web3.PublicKey) {
   return anchor.web3.PublicKey.findProgramAddress(
     [Buffer.from("metadata"), METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
@@ -123,6 +224,22 @@ export async function findMetadataAddress(mint: anchor.web3.PublicKey) {
   );
 }
 
+export async function findBundleLoanAddress(
+  borrower: anchor.web3.PublicKey,
+  id: anchor.BN
+): Promise<anchor.web3.PublicKey> {
+  const [bundleLoanAddress] = await anchor.web3.PublicKey.findProgramAddress(
+    [
+      Buffer.from("bundle_loan"),
+      borrower.toBuffer(),
+      id.toArrayLike(Buffer, "le", 8),
+    ],
+    PROGRAM_ID
+  );
+
+  return bundleLoanAddress;
+}
+
 export type LoanBorrower = Awaited<ReturnType<typeof initLoan>>;
 export type LoanLender = Awaited<ReturnType<typeof giveLoan>>;
 
@@ -132,6 +249,17 @@ export async function initLoan(
     amount: number;
     basisPoints: number;
     duration: number;
+    interestMode?: "fullTerm" | "proRata" | "proRataWithMinimum";
+    minimumFee?: number;
+    currencyMint?: anchor.web3.PublicKey;
+    liquidationThreshold?: number;
+    priceFeed?: anchor.web3.PublicKey;
+    gracePeriod?: number;
+    lateFeeBasisPoints?: number;
+    defaultMode?: any;
+    noticePeriod?: number;
+    listingExpiry?: number;
+    counterparty?: anchor.web3.PublicKey;
   }
 ) {
   const keypair = anchor.web3.Keypair.generate();
@@ -168,10 +296,42 @@ export async function initLoan(
   const amount = new anchor.BN(options.amount);
   const basisPoints = new anchor.BN(options.basisPoints);
   const duration = new anchor.BN(options.duration);
+  const interestMode = { [options.interestMode ?? "fullTerm"]: {} };
+  const minimumFee = new anchor.BN(options.minimumFee ?? 0);
+  const currencyMint = options.currencyMint ?? null;
+  const liquidationThreshold = options.liquidationThreshold ?? null;
+  const priceFeed = options.priceFeed ?? null;
+  const gracePeriod = new anchor.BN(options.gracePeriod ?? 0);
+  const lateFeeBasisPoints = options.lateFeeBasisPoints ?? 0;
+  const defaultMode = options.defaultMode ?? { repossess: {} };
+  const noticePeriod =
+    options.noticePeriod !== undefined
+      ? new anchor.BN(options.noticePeriod)
+      : null;
+  const listingExpiry =
+    options.listingExpiry !== undefined
+      ? new anchor.BN(options.listingExpiry)
+      : null;
+  const counterparty = options.counterparty ?? null;
 
   try {
     await program.methods
-      .initLoan(amount, basisPoints, duration)
+      .initLoan({
+        amount,
+        basisPoints,
+        duration,
+        interestMode,
+        minimumFee,
+        currencyMint,
+        liquidationThreshold,
+        priceFeed,
+        gracePeriod,
+        lateFeeBasisPoints,
+        defaultMode,
+        noticePeriod,
+        listingExpiry,
+        counterparty,
+      })
       .accounts({
         tokenManager,
         depositTokenAccount,
@@ -202,15 +362,107 @@ export async function initLoan(
   };
 }
 
-export async function giveLoan(
+export type BundleBorrower = Awaited<ReturnType<typeof initBundleLoan>>;
+
+export function getBundleRemainingAccounts(
+  nfts: BundleBorrower["nfts"]
+): anchor.web3.AccountMeta[] {
+  return nfts.flatMap((nft) => [
+    { pubkey: nft.mint, isSigner: false, isWritable: false },
+    { pubkey: nft.edition, isSigner: false, isWritable: false },
+    { pubkey: nft.depositTokenAccount, isSigner: false, isWritable: true },
+    { pubkey: nft.tokenManager, isSigner: false, isWritable: true },
+  ]);
+}
+
+export async function initBundleLoan(
   connection: anchor.web3.Connection,
-  borrower: Awaited<ReturnType<typeof initLoan>>
+  options: {
+    amount: number;
+    basisPoints: number;
+    duration: number;
+    count: number;
+  }
 ) {
   const keypair = anchor.web3.Keypair.generate();
   const provider = getProvider(connection, keypair);
   const program = getProgram(provider);
   await requestAirdrop(connection, keypair.publicKey);
 
+  const metaplex = Metaplex.make(connection).use(keypairIdentity(keypair));
+
+  const nfts = [];
+
+  for (let i = 0; i < options.count; i++) {
+    const { nft } = await metaplex
+      .nfts()
+      .create({
+        uri: "https://arweave.net/123",
+        name: "My NFT",
+        sellerFeeBasisPoints: 500,
+      })
+      .run();
+
+    const largestAccounts = await connection.getTokenLargestAccounts(
+      nft.mint.address
+    );
+
+    nfts.push({
+      mint: nft.mint.address,
+      edition: nft.edition.address,
+      depositTokenAccount: largestAccounts.value[0].address,
+      tokenManager: await findTokenManagerAddress(
+        nft.mint.address,
+        keypair.publicKey
+      ),
+    });
+  }
+
+  const id = new anchor.BN(0);
+  const bundleLoan = await findBundleLoanAddress(keypair.publicKey, id);
+
+  try {
+    await program.methods
+      .initBundleLoan({
+        id,
+        amount: new anchor.BN(options.amount),
+        basisPoints: options.basisPoints,
+        duration: new anchor.BN(options.duration),
+      })
+      .accounts({
+        bundleLoan,
+        borrower: keypair.publicKey,
+        metadataProgram: METADATA_PROGRAM_ID,
+        tokenProgram: splToken.TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts(getBundleRemainingAccounts(nfts))
+      .rpc();
+  } catch (error) {
+    console.log(error.logs);
+    throw error;
+  }
+
+  return {
+    keypair,
+    provider,
+    program,
+    bundleLoan,
+    nfts,
+  };
+}
+
+export async function giveLoan(
+  connection: anchor.web3.Connection,
+  borrower: Awaited<ReturnType<typeof initLoan>>,
+  remainingAccounts: anchor.web3.AccountMeta[] = [],
+  keypair = anchor.web3.Keypair.generate()
+) {
+  const provider = getProvider(connection, keypair);
+  const program = getProgram(provider);
+  await requestAirdrop(connection, keypair.publicKey);
+
   try {
     await program.methods
       .giveLoan()
@@ -224,6 +476,81 @@ export async function giveLoan(
         tokenProgram: splToken.TOKEN_PROGRAM_ID,
         clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
       })
+      .remainingAccounts(remainingAccounts)
+      .rpc();
+  } catch (error) {
+    console.log(error.logs);
+    throw error;
+  }
+
+  return {
+    keypair,
+    provider,
+    program,
+  };
+}
+
+export async function findLoanNoteAddress(
+  loan: anchor.web3.PublicKey,
+  startDate: anchor.BN
+): Promise<anchor.web3.PublicKey> {
+  const [loanNoteAddress] = await anchor.web3.PublicKey.findProgramAddress(
+    [
+      Buffer.from("loan_note"),
+      loan.toBuffer(),
+      startDate.toArrayLike(Buffer, "le", 8),
+    ],
+    PROGRAM_ID
+  );
+
+  return loanNoteAddress;
+}
+
+export async function findLoanOfferAddress(
+  mint: anchor.web3.PublicKey,
+  lender: anchor.web3.PublicKey
+): Promise<anchor.web3.PublicKey> {
+  const [loanOfferAddress] = await anchor.web3.PublicKey.findProgramAddress(
+    [Buffer.from("loan_offer"), mint.toBuffer(), lender.toBuffer()],
+    PROGRAM_ID
+  );
+
+  return loanOfferAddress;
+}
+
+export async function initLoanOffer(
+  connection: anchor.web3.Connection,
+  mint: anchor.web3.PublicKey,
+  options: {
+    amount: number;
+    basisPoints: number;
+    duration: number;
+    expiry: number;
+  }
+) {
+  const keypair = anchor.web3.Keypair.generate();
+  const provider = getProvider(connection, keypair);
+  const program = getProgram(provider);
+  await requestAirdrop(connection, keypair.publicKey);
+
+  const loanOffer = await findLoanOfferAddress(mint, keypair.publicKey);
+
+  try {
+    await program.methods
+      .initLoanOffer({
+        amount: new anchor.BN(options.amount),
+        basisPoints: new anchor.BN(options.basisPoints),
+        duration: new anchor.BN(options.duration),
+        expiry: new anchor.BN(options.expiry),
+      })
+      .accounts({
+        loanOffer,
+        mint,
+        lender: keypair.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
       .rpc();
   } catch (error) {
     console.log(error.logs);
@@ -234,9 +561,27 @@ export async function giveLoan(
     keypair,
     provider,
     program,
+    loanOffer,
   };
 }
 
+export async function findCollectionLoanOfferAddress(
+  collection: anchor.web3.PublicKey,
+  lender: anchor.web3.PublicKey
+): Promise<anchor.web3.PublicKey> {
+  const [collectionLoanOfferAddress] =
+    await anchor.web3.PublicKey.findProgramAddress(
+      [
+        Buffer.from("collection_loan_offer"),
+        collection.toBuffer(),
+        lender.toBuffer(),
+      ],
+      PROGRAM_ID
+    );
+
+  return collectionLoanOfferAddress;
+}
+
 export type CallOptionSeller = Awaited<ReturnType<typeof initCallOption>>;
 export type CallOptionBuyer = Awaited<ReturnType<typeof buyCallOption>>;
 
@@ -274,6 +619,10 @@ export async function initCallOption(
     amount: number;
     strikePrice: number;
     expiry: number;
+    listingExpiry?: number;
+    counterparty?: anchor.web3.PublicKey;
+    exerciseStyle?: any;
+    currencyMint?: anchor.web3.PublicKey;
   }
 ) {
   const keypair = anchor.web3.Keypair.generate();
@@ -297,13 +646,22 @@ export async function initCallOption(
     keypair.publicKey
   );
 
-  const amount = new anchor.BN(options.amount);
-  const strikePrice = new anchor.BN(options.strikePrice);
-  const expiry = new anchor.BN(options.expiry);
+  const args = {
+    amount: new anchor.BN(options.amount),
+    strikePrice: new anchor.BN(options.strikePrice),
+    expiry: new anchor.BN(options.expiry),
+    listingExpiry:
+      options.listingExpiry !== undefined
+        ? new anchor.BN(options.listingExpiry)
+        : null,
+    counterparty: options.counterparty ?? null,
+    exerciseStyle: options.exerciseStyle ?? { american: {} },
+    currencyMint: options.currencyMint ?? null,
+  };
 
   try {
     await program.methods
-      .initCallOption(amount, strikePrice, expiry)
+      .initCallOption(args)
       .accounts({
         tokenManager,
         callOption: callOptionAccount,
@@ -348,6 +706,8 @@ export async function buyCallOption(
     const signature = await program.methods
       .buyCallOption()
       .accounts({
+        protocolConfig: await findProtocolConfigAddress(),
+        treasury: treasury.publicKey,
         seller: seller.keypair.publicKey,
         buyer: keypair.publicKey,
         callOption: seller.callOptionAccount,
@@ -378,6 +738,98 @@ export async function buyCallOption(
   };
 }
 
+export type PutOptionWriter = Awaited<ReturnType<typeof initPutOption>>;
+
+export async function initPutOption(
+  connection: anchor.web3.Connection,
+  mint: anchor.web3.PublicKey,
+  options: {
+    amount: number;
+    strikePrice: number;
+    expiry: number;
+  }
+) {
+  const keypair = anchor.web3.Keypair.generate();
+  const provider = getProvider(connection, keypair);
+  const program = getProgram(provider);
+  await requestAirdrop(connection, keypair.publicKey);
+
+  const putOptionAccount = await findPutOptionAddress(mint, keypair.publicKey);
+
+  try {
+    await program.methods
+      .initPutOption(
+        new anchor.BN(options.amount),
+        new anchor.BN(options.strikePrice),
+        new anchor.BN(options.expiry)
+      )
+      .accounts({
+        mint,
+        putOption: putOptionAccount,
+        writer: keypair.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+  } catch (error) {
+    console.log(error.logs);
+    throw error;
+  }
+
+  return {
+    keypair,
+    provider,
+    program,
+    mint,
+    putOptionAccount,
+    options,
+  };
+}
+
+export async function buyPutOption(
+  connection: anchor.web3.Connection,
+  keypair: anchor.web3.Keypair,
+  writer: PutOptionWriter,
+  expected: {
+    amount: number;
+    strikePrice: number;
+    expiry: number;
+  } = writer.options
+) {
+  const provider = getProvider(connection, keypair);
+  const program = getProgram(provider);
+
+  try {
+    await program.methods
+      .buyPutOption(
+        new anchor.BN(expected.strikePrice),
+        new anchor.BN(expected.amount),
+        new anchor.BN(expected.expiry)
+      )
+      .accounts({
+        protocolConfig: await findProtocolConfigAddress(),
+        treasury: treasury.publicKey,
+        writer: writer.keypair.publicKey,
+        buyer: keypair.publicKey,
+        putOption: writer.putOptionAccount,
+        mint: writer.mint,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        clock: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+      })
+      .rpc();
+  } catch (error) {
+    console.log(error.logs);
+    throw error;
+  }
+
+  return {
+    keypair,
+    provider,
+    program,
+  };
+}
+
 export type HireLender = Awaited<ReturnType<typeof initHire>>;
 export type HireBorrower = Awaited<ReturnType<typeof takeHire>>;
 
@@ -483,6 +935,8 @@ export async function takeHire(
     await program.methods
       .takeHire(days)
       .accounts({
+        protocolConfig: await findProtocolConfigAddress(),
+        treasury: treasury.publicKey,
         borrower: keypair.publicKey,
         lender: lender.keypair.publicKey,
         hire: lender.hire,
@@ -524,6 +978,8 @@ export async function recoverHire(lender: HireLender, borrower: HireBorrower) {
     await lender.program.methods
       .recoverHire()
       .accounts({
+        protocolConfig: await findProtocolConfigAddress(),
+        treasury: treasury.publicKey,
         borrower: borrower.keypair.publicKey,
         lender: lender.keypair.publicKey,
         hire: lender.hire,